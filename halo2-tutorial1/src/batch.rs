@@ -0,0 +1,127 @@
+#![allow(dead_code)]
+
+use halo2_proofs::{
+    arithmetic::Field,
+    pairing::arithmetic::Engine,
+    pairing::bn256::{Bn256, Fr, G1Affine},
+    pairing::group::Curve,
+    plonk::{verify_proof, Error, PairMSM, VerificationStrategy, VerifyingKey},
+    poly::commitment::ParamsVerifier,
+    transcript::{Blake2bRead, Challenge255, EncodedChallenge},
+};
+use rand_core::OsRng;
+
+use crate::keccak::Keccak256Read;
+use crate::prover::TranscriptKind;
+
+/// Hands back a proof's pending pairing check instead of running it.
+struct Deferred;
+
+impl<'params> VerificationStrategy<'params, Bn256> for Deferred {
+    type Output = PairMSM<'params, Bn256>;
+
+    fn process<Ch: EncodedChallenge<G1Affine>>(
+        self,
+        f: impl FnOnce() -> Result<PairMSM<'params, Bn256>, Error>,
+    ) -> Result<Self::Output, Error> {
+        f()
+    }
+}
+
+/// Verifies many proofs for the same verifying key with one pairing check.
+/// If that check fails, the failing proofs are found by bisection, at the
+/// cost of about two checks per failing proof for each halving.
+pub struct BatchVerifier<'a> {
+    params_verifier: &'a ParamsVerifier<Bn256>,
+    vk: &'a VerifyingKey<G1Affine>,
+    /// The transcript every proof in the batch was made with.
+    transcript: TranscriptKind,
+    proofs: Vec<(Vec<Vec<Fr>>, Vec<u8>)>,
+}
+
+impl<'a> BatchVerifier<'a> {
+    pub fn new(
+        params_verifier: &'a ParamsVerifier<Bn256>,
+        vk: &'a VerifyingKey<G1Affine>,
+        transcript: TranscriptKind,
+    ) -> Self {
+        Self {
+            params_verifier,
+            vk,
+            transcript,
+            proofs: vec![],
+        }
+    }
+
+    /// Queues `proof` against `instances`, one vector per instance column.
+    pub fn add(&mut self, instances: Vec<Vec<Fr>>, proof: Vec<u8>) {
+        self.proofs.push((instances, proof));
+    }
+
+    pub fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    /// Returns the indices of the proofs that do not verify, in the order
+    /// they were added. All proofs verified if it is empty.
+    pub fn verify(&self) -> Vec<usize> {
+        let indices: Vec<usize> = (0..self.proofs.len()).collect();
+        let mut failed = vec![];
+        self.bisect(&indices, &mut failed);
+        failed
+    }
+
+    fn bisect(&self, indices: &[usize], failed: &mut Vec<usize>) {
+        if indices.is_empty() || self.check(indices) {
+            return;
+        }
+        if let [index] = indices {
+            failed.push(*index);
+            return;
+        }
+        let (left, right) = indices.split_at(indices.len() / 2);
+        self.bisect(left, failed);
+        self.bisect(right, failed);
+    }
+
+    /// Reads the proofs at `indices` and runs their pairing checks as one.
+    /// Each check is scaled by a fresh random factor first, so that errors in
+    /// two proofs cannot be made to cancel out.
+    fn check(&self, indices: &[usize]) -> bool {
+        let mut acc: Option<PairMSM<Bn256>> = None;
+        for index in indices {
+            let (instances, proof) = &self.proofs[*index];
+            let columns: Vec<&[Fr]> = instances.iter().map(|column| column.as_slice()).collect();
+            let msm = match self.transcript {
+                TranscriptKind::Blake2b => {
+                    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof.as_slice());
+                    verify_proof(self.params_verifier, self.vk, Deferred, &[&columns], &mut transcript)
+                }
+                TranscriptKind::Keccak256 => {
+                    let mut transcript = Keccak256Read::<_, _, Challenge255<_>>::init(proof.as_slice());
+                    verify_proof(self.params_verifier, self.vk, Deferred, &[&columns], &mut transcript)
+                }
+            };
+            let mut msm = match msm {
+                Ok(msm) => msm,
+                // The proof could not be read, so there is nothing to defer.
+                Err(_) => return false,
+            };
+            msm.scale(Fr::random(OsRng));
+            match acc.as_mut() {
+                Some(acc) => acc.add_msm(msm),
+                None => acc = Some(msm),
+            }
+        }
+
+        match acc {
+            Some(acc) => {
+                // e(left, [s]_2) = e(right, [1]_2), as `SingleVerifier` checks.
+                let (left, right) = acc.eval();
+                Bn256::pairing(&left.to_affine(), &self.params_verifier.s_g2)
+                    == Bn256::pairing(&right.to_affine(), &self.params_verifier.g2)
+            }
+            None => true,
+        }
+    }
+}
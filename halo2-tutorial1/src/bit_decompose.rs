@@ -0,0 +1,150 @@
+use halo2_tutorial::{bits, prover};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+
+use bits::{le_bits, num_bits, BitOrder, BitsChip, BitsConfig, BitsInstructions};
+
+/// Decomposes `value` into `n` bits in `order`, recomposes them, and exposes
+/// the recomposed value on instance row 0 followed by the bits. `forged`
+/// replaces the honest bits, most significant first.
+struct DecomposeCircuit<F> {
+    value: Option<F>,
+    n: usize,
+    order: BitOrder,
+    strict: bool,
+    forged: Option<Vec<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for DecomposeCircuit<F> {
+    type Config = BitsConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            value: None,
+            n: self.n,
+            order: self.order,
+            strict: self.strict,
+            forged: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        BitsChip::configure(meta, advice, instance, constant)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = BitsChip::construct(config);
+
+        let value = chip.load_private(layouter.namespace(|| "load value"), self.value)?;
+        let bits = match (&self.forged, self.strict) {
+            (Some(bits), strict) => {
+                let bits: Vec<Option<F>> = bits.iter().map(|b| Some(*b)).collect();
+                chip.assign_decompose(layouter.namespace(|| "decompose"), &value, &bits, strict, self.order)?
+            }
+            (None, true) => chip.decompose_strict(layouter.namespace(|| "decompose"), &value, self.order)?,
+            (None, false) => chip.decompose(layouter.namespace(|| "decompose"), &value, self.n, self.order)?,
+        };
+        let recomposed = chip.recompose(layouter.namespace(|| "recompose"), &bits, self.order)?;
+
+        chip.expose_public(layouter.namespace(|| "expose value"), &recomposed, 0)?;
+        for (i, bit) in bits.iter().enumerate() {
+            chip.expose_public(layouter.namespace(|| format!("expose bit {}", i)), &bit.0, i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// `value` followed by its lowest `n` bits in `order`.
+fn public_inputs(value: Fp, n: usize, order: BitOrder) -> Vec<Fp> {
+    let mut bits: Vec<Fp> = le_bits(value, n).into_iter().map(|b| Fp::from(b as u64)).collect();
+    if order == BitOrder::MsbFirst {
+        bits.reverse();
+    }
+    std::iter::once(value).chain(bits).collect()
+}
+
+/// The bits of `p + small`, most significant first. This is a second
+/// `NUM_BITS`-bit decomposition of `small`.
+fn non_canonical_bits(small: u64) -> Vec<Fp> {
+    let n = num_bits::<Fp>();
+    let p_minus_one = le_bits(-Fp::one(), n);
+    let mut carry = 0u64;
+    let mut bits = vec![];
+    for i in 0..n {
+        // p + small = (p - 1) + (small + 1)
+        let addend = if i < 64 { ((small + 1) >> i) & 1 } else { 0 };
+        let sum = p_minus_one[i] as u64 + addend + carry;
+        bits.push(Fp::from(sum & 1));
+        carry = sum >> 1;
+    }
+    assert_eq!(carry, 0);
+    bits.reverse();
+    bits
+}
+
+fn main() {
+    let k = 5;
+
+    // 11 = 0b00001011
+    for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+        let value = Fp::from(11);
+        let circuit = DecomposeCircuit { value: Some(value), n: 8, order, strict: false, forged: None };
+        let public_inputs = public_inputs(value, 8, order);
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{:?}", order);
+
+        // Bits in the other order are rejected.
+        let mut swapped = public_inputs.clone();
+        swapped[1..].reverse();
+        let prover = MockProver::run(k, &circuit, vec![swapped]).unwrap();
+        assert!(prover.verify().is_err(), "{:?}", order);
+    }
+    let expected: Vec<Fp> = [11, 0, 0, 0, 0, 1, 0, 1, 1].iter().map(|v| Fp::from(*v)).collect();
+    assert_eq!(public_inputs(Fp::from(11), 8, BitOrder::MsbFirst), expected);
+
+    let circuit = DecomposeCircuit { value: Some(Fp::from(11)), n: 8, order: BitOrder::MsbFirst, strict: false, forged: None };
+    println!("{}", prover::round_trip(k, &circuit, &[expected]).unwrap());
+
+    // 300 does not fit in 8 bits.
+    let value = Fp::from(300);
+    let circuit = DecomposeCircuit { value: Some(value), n: 8, order: BitOrder::LsbFirst, strict: false, forged: None };
+    let prover = MockProver::run(k, &circuit, vec![public_inputs(value, 8, BitOrder::LsbFirst)]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // A non-boolean bit: 3 = 2 * 0 + 3.
+    let value = Fp::from(3);
+    let forged = vec![Fp::zero(), Fp::zero(), Fp::zero(), Fp::from(3)];
+    let circuit = DecomposeCircuit { value: Some(value), n: 4, order: BitOrder::MsbFirst, strict: false, forged: Some(forged.clone()) };
+    let prover = MockProver::run(k, &circuit, vec![std::iter::once(value).chain(forged).collect()]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Full-width decompositions, including p - 1, pass the strict check.
+    let k = 10;
+    let n = num_bits::<Fp>();
+    for value in [Fp::zero(), Fp::from(5), -Fp::one()] {
+        let circuit = DecomposeCircuit { value: Some(value), n, order: BitOrder::LsbFirst, strict: true, forged: None };
+        let public_inputs = public_inputs(value, n, BitOrder::LsbFirst);
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    // The bits of p + 5 also sum to 5 in the field. Only strict mode catches it.
+    let value = Fp::from(5);
+    let forged = non_canonical_bits(5);
+    let public_inputs: Vec<Fp> = std::iter::once(value).chain(forged.iter().copied()).collect();
+    for (strict, ok) in [(false, true), (true, false)] {
+        let circuit = DecomposeCircuit { value: Some(value), n, order: BitOrder::MsbFirst, strict, forged: Some(forged.clone()) };
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify().is_ok(), ok, "strict = {}", strict);
+    }
+}
@@ -0,0 +1,347 @@
+#![allow(dead_code)]
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::boolean::Bit;
+use crate::expr::{bool_check, Expr};
+
+/// The order of the bits returned by a decomposition, or passed to a
+/// recomposition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+/// The number of bits in a canonical decomposition.
+pub fn num_bits<F: FieldExt>() -> usize {
+    F::NUM_BITS as usize
+}
+
+/// Returns the lowest `n` bits of `value`, least significant first.
+pub fn le_bits<F: FieldExt>(value: F, n: usize) -> Vec<bool> {
+    let repr = value.to_repr();
+    let bytes = repr.as_ref();
+    (0..n)
+        .map(|i| bytes.get(i / 8).map_or(false, |byte| (byte >> (i % 8)) & 1 == 1))
+        .collect()
+}
+
+pub trait BitsInstructions<F: FieldExt>: Chip<F> {
+    /// Loads a number into the circuit as a private input.
+    fn load_private(&self, layouter: impl Layouter<F>, value: Option<F>) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Decomposes `value` into `n` bits. The circuit is unsatisfiable if
+    /// `value` does not fit in `n` bits. Below `F::NUM_BITS` bits the
+    /// decomposition is unique; at `F::NUM_BITS` use `decompose_strict`.
+    fn decompose(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        n: usize,
+        order: BitOrder,
+    ) -> Result<Vec<Bit<F>>, Error>;
+
+    /// Decomposes `value` into `F::NUM_BITS` bits and proves that the bits,
+    /// read as an integer, are below the field modulus.
+    fn decompose_strict(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        order: BitOrder,
+    ) -> Result<Vec<Bit<F>>, Error>;
+
+    /// Returns the number whose bits are `bits`.
+    fn recompose(
+        &self,
+        layouter: impl Layouter<F>,
+        bits: &[Bit<F>],
+        order: BitOrder,
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct BitsConfig {
+    pub bit: Column<Advice>,
+    pub acc: Column<Advice>,
+    /// Whether the bits so far equal the bits of `p - 1`.
+    eq: Column<Advice>,
+    /// Whether the bits so far are already below the bits of `p - 1`.
+    lt: Column<Advice>,
+    /// The bits of `p - 1`, most significant first.
+    modulus: Column<Fixed>,
+    pub instance: Column<Instance>,
+    s_bits: Selector,
+    s_canonical: Selector,
+    s_le_modulus: Selector,
+}
+
+pub struct BitsChip<F: FieldExt> {
+    config: BitsConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for BitsChip<F> {
+    type Config = BitsConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> BitsChip<F> {
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> <Self as Chip<F>>::Config {
+        let [bit, acc, eq, lt] = advice;
+        let modulus = meta.fixed_column();
+        let s_bits = meta.selector();
+        let s_canonical = meta.selector();
+        let s_le_modulus = meta.selector();
+
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for col in &advice {
+            meta.enable_equality(*col);
+        }
+
+        // Bits are laid out most significant first, whatever order the caller
+        // asked for:
+        //
+        // | bit |  acc  | eq | lt | modulus | s_bits | s_canonical | s_le_modulus |
+        // |-----|-------|----|----|---------|--------|-------------|--------------|
+        // | b_0 |   0   | 1  | 0  |   m_0   |   1    |      1      |              |
+        // | b_1 | acc_1 | .. | .. |   m_1   |   1    |      1      |              |
+        // | ... |  ...  | .. | .. |   ...   |  ...   |     ...     |              |
+        // |     | value | eq | lt |         |        |             |      1       |
+        meta.create_gate("bits", |meta| {
+            let s = meta.query_selector(s_bits);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            vec![
+                s.clone() * bool_check(bit.clone()),
+                s * (acc_cur * Expression::Constant(F::from(2)) + bit - acc_next),
+            ]
+        });
+
+        // Compares the bits against `p - 1` from the top: `lt` latches once a
+        // bit is 0 where the modulus has a 1 while still equal, and `eq`
+        // clears at the first differing bit.
+        meta.create_gate("canonical", |meta| {
+            let s = meta.query_selector(s_canonical);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let m = meta.query_fixed(modulus, Rotation::cur());
+            let eq_cur = meta.query_advice(eq, Rotation::cur());
+            let eq_next = meta.query_advice(eq, Rotation::next());
+            let lt_cur = meta.query_advice(lt, Rotation::cur());
+            let lt_next = meta.query_advice(lt, Rotation::next());
+            vec![
+                s.clone() * (lt_cur + eq_cur.and(bit.not()).and(m.clone()) - lt_next),
+                s * (eq_cur.and(bit.xor(m).not()) - eq_next),
+            ]
+        });
+
+        meta.create_gate("le modulus", |meta| {
+            let s = meta.query_selector(s_le_modulus);
+            let eq = meta.query_advice(eq, Rotation::cur());
+            let lt = meta.query_advice(lt, Rotation::cur());
+            vec![s * (eq + lt - Expression::Constant(F::one()))]
+        });
+
+        BitsConfig {
+            bit,
+            acc,
+            eq,
+            lt,
+            modulus,
+            instance,
+            s_bits,
+            s_canonical,
+            s_le_modulus,
+        }
+    }
+
+    /// Assigns the running sum over `bits`, most significant first, and
+    /// returns the bit cells and the final sum.
+    fn assign_bits(
+        &self,
+        region: &mut Region<'_, F>,
+        bits: &[Option<F>],
+    ) -> Result<(Vec<Bit<F>>, AssignedCell<F, F>), Error> {
+        let config = self.config();
+        let mut acc = region.assign_advice_from_constant(|| "acc", config.acc, 0, F::zero())?;
+        let mut cells = Vec::with_capacity(bits.len());
+
+        for (i, bit) in bits.iter().enumerate() {
+            config.s_bits.enable(region, i)?;
+            let cell = region.assign_advice(|| format!("bit {}", i), config.bit, i, || bit.ok_or(Error::Synthesis))?;
+
+            let next = acc.value().zip(*bit).map(|(acc, bit)| acc.double() + bit);
+            acc = region.assign_advice(|| "acc", config.acc, i + 1, || next.ok_or(Error::Synthesis))?;
+            cells.push(Bit(cell));
+        }
+
+        Ok((cells, acc))
+    }
+
+    /// Assigns the comparison of `bits` against `p - 1`.
+    fn assign_canonical(&self, region: &mut Region<'_, F>, bits: &[Option<F>]) -> Result<(), Error> {
+        let config = self.config();
+        let mut modulus = le_bits(-F::one(), bits.len());
+        modulus.reverse();
+
+        let mut eq = region.assign_advice_from_constant(|| "eq", config.eq, 0, F::one())?;
+        let mut lt = region.assign_advice_from_constant(|| "lt", config.lt, 0, F::zero())?;
+
+        for (i, (bit, m)) in bits.iter().zip(modulus).enumerate() {
+            config.s_canonical.enable(region, i)?;
+            let m = F::from(m as u64);
+            region.assign_fixed(|| "modulus bit", config.modulus, i, || Ok(m))?;
+
+            let values = eq.value().zip(lt.value()).zip(*bit);
+            let next_lt = values.map(|((eq, lt), bit)| *lt + *eq * (F::one() - bit) * m);
+            let next_eq = values.map(|((eq, _), bit)| *eq * (F::one() - bit - m + bit * m.double()));
+            eq = region.assign_advice(|| "eq", config.eq, i + 1, || next_eq.ok_or(Error::Synthesis))?;
+            lt = region.assign_advice(|| "lt", config.lt, i + 1, || next_lt.ok_or(Error::Synthesis))?;
+        }
+
+        config.s_le_modulus.enable(region, bits.len())
+    }
+
+    /// Assigns a decomposition of `value` with `bits`, most significant
+    /// first, as the claimed witness. The instructions compute `bits`
+    /// honestly; tests use this directly to check that forged bits are
+    /// rejected.
+    pub fn assign_decompose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        bits: &[Option<F>],
+        strict: bool,
+        order: BitOrder,
+    ) -> Result<Vec<Bit<F>>, Error> {
+        if bits.is_empty() || (strict && bits.len() != num_bits::<F>()) {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "decompose",
+            |mut region: Region<'_, F>| {
+                let (mut cells, acc) = self.assign_bits(&mut region, bits)?;
+                region.constrain_equal(acc.cell(), value.cell())?;
+                if strict {
+                    self.assign_canonical(&mut region, bits)?;
+                }
+
+                if order == BitOrder::LsbFirst {
+                    cells.reverse();
+                }
+                Ok(cells)
+            },
+        )
+    }
+
+    fn witness(value: &AssignedCell<F, F>, n: usize) -> Vec<Option<F>> {
+        let mut bits: Vec<Option<F>> = match value.value() {
+            Some(v) => le_bits(*v, n).into_iter().map(|b| Some(F::from(b as u64))).collect(),
+            None => vec![None; n],
+        };
+        bits.reverse();
+        bits
+    }
+}
+
+impl<F: FieldExt> BitsInstructions<F> for BitsChip<F> {
+    fn load_private(&self, mut layouter: impl Layouter<F>, value: Option<F>) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "load private",
+            |mut region| region.assign_advice(|| "private input", config.acc, 0, || value.ok_or(Error::Synthesis)),
+        )
+    }
+
+    fn decompose(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        n: usize,
+        order: BitOrder,
+    ) -> Result<Vec<Bit<F>>, Error> {
+        let bits = Self::witness(value, n);
+        self.assign_decompose(layouter, value, &bits, false, order)
+    }
+
+    fn decompose_strict(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        order: BitOrder,
+    ) -> Result<Vec<Bit<F>>, Error> {
+        let bits = Self::witness(value, num_bits::<F>());
+        self.assign_decompose(layouter, value, &bits, true, order)
+    }
+
+    fn recompose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bits: &[Bit<F>],
+        order: BitOrder,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let mut bits = bits.to_vec();
+        if order == BitOrder::LsbFirst {
+            bits.reverse();
+        }
+        let values: Vec<Option<F>> = bits.iter().map(|b| b.0.value().copied()).collect();
+
+        layouter.assign_region(
+            || "recompose",
+            |mut region: Region<'_, F>| {
+                let (cells, acc) = self.assign_bits(&mut region, &values)?;
+                for (bit, cell) in bits.iter().zip(cells) {
+                    region.constrain_equal(bit.0.cell(), cell.0.cell())?;
+                }
+                Ok(acc)
+            },
+        )
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
@@ -0,0 +1,303 @@
+#![allow(dead_code)]
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::expr::{bool_check, Expr};
+
+/// A cell constrained to be 0 or 1.
+#[derive(Clone, Debug)]
+pub struct Bit<F: FieldExt>(pub AssignedCell<F, F>);
+
+/// The operations implemented by `BooleanChip`, each with its own selector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoolOp {
+    And,
+    Or,
+    Xor,
+    Nand,
+    Not,
+}
+
+impl BoolOp {
+    /// Evaluates the operation natively on bits.
+    pub fn eval(&self, a: bool, b: bool) -> bool {
+        match self {
+            BoolOp::And => a & b,
+            BoolOp::Or => a | b,
+            BoolOp::Xor => a ^ b,
+            BoolOp::Nand => !(a & b),
+            BoolOp::Not => !a,
+        }
+    }
+}
+
+pub trait BooleanInstructions<F: FieldExt>: Chip<F> {
+    /// Loads a private bit. The circuit is unsatisfiable unless it is 0 or 1.
+    fn assign_bit(&self, layouter: impl Layouter<F>, value: Option<F>) -> Result<Bit<F>, Error>;
+
+    /// Loads a private bit vector.
+    fn assign_bits(&self, layouter: impl Layouter<F>, values: &[Option<F>]) -> Result<Vec<Bit<F>>, Error>;
+
+    /// Returns `a AND b`.
+    fn and(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error>;
+
+    /// Returns `a OR b`.
+    fn or(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error>;
+
+    /// Returns `a XOR b`.
+    fn xor(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error>;
+
+    /// Returns `NOT (a AND b)`.
+    fn nand(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error>;
+
+    /// Returns `NOT a`.
+    fn not(&self, layouter: impl Layouter<F>, a: &Bit<F>) -> Result<Bit<F>, Error>;
+
+    /// Applies `op` bitwise. `b` is ignored for `Not`, and must otherwise be
+    /// as long as `a`.
+    fn bitwise(
+        &self,
+        layouter: impl Layouter<F>,
+        op: BoolOp,
+        a: &[Bit<F>],
+        b: &[Bit<F>],
+    ) -> Result<Vec<Bit<F>>, Error>;
+
+    /// Exposes a bit as a public input to the circuit.
+    fn expose_public(&self, layouter: impl Layouter<F>, bit: &Bit<F>, row: usize) -> Result<(), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct BooleanConfig {
+    pub advice: [Column<Advice>; 3],
+    pub instance: Column<Instance>,
+    s_bit: Selector,
+    s_and: Selector,
+    s_or: Selector,
+    s_xor: Selector,
+    s_nand: Selector,
+    s_not: Selector,
+}
+
+impl BooleanConfig {
+    fn selector(&self, op: BoolOp) -> Selector {
+        match op {
+            BoolOp::And => self.s_and,
+            BoolOp::Or => self.s_or,
+            BoolOp::Xor => self.s_xor,
+            BoolOp::Nand => self.s_nand,
+            BoolOp::Not => self.s_not,
+        }
+    }
+}
+
+pub struct BooleanChip<F: FieldExt> {
+    config: BooleanConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for BooleanChip<F> {
+    type Config = BooleanConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> BooleanChip<F> {
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+    ) -> <Self as Chip<F>>::Config {
+        meta.enable_equality(instance);
+        for col in &advice {
+            meta.enable_equality(*col);
+        }
+
+        let config = BooleanConfig {
+            advice,
+            instance,
+            s_bit: meta.selector(),
+            s_and: meta.selector(),
+            s_or: meta.selector(),
+            s_xor: meta.selector(),
+            s_nand: meta.selector(),
+            s_not: meta.selector(),
+        };
+
+        meta.create_gate("bit", |meta| {
+            let s = meta.query_selector(config.s_bit);
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            vec![s * bool_check(a)]
+        });
+
+        // All operations share one layout, one bit per row:
+        //
+        // |  a0 |  a1 |  a2 | selector |
+        // |-----|-----|-----|----------|
+        // | lhs | rhs | out |    s     |
+        //
+        // `lhs` and `rhs` are copied from bits, so `out` is a bit as well. `not`
+        // leaves `rhs` unassigned and never queries it.
+        let ops: [(&'static str, BoolOp); 5] = [
+            ("and", BoolOp::And),
+            ("or", BoolOp::Or),
+            ("xor", BoolOp::Xor),
+            ("nand", BoolOp::Nand),
+            ("not", BoolOp::Not),
+        ];
+        for (name, op) in ops {
+            let selector = config.selector(op);
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let lhs = meta.query_advice(advice[0], Rotation::cur());
+                let out = meta.query_advice(advice[2], Rotation::cur());
+                let poly = match op {
+                    BoolOp::And => lhs.and(meta.query_advice(advice[1], Rotation::cur())),
+                    BoolOp::Or => lhs.or(meta.query_advice(advice[1], Rotation::cur())),
+                    BoolOp::Xor => lhs.xor(meta.query_advice(advice[1], Rotation::cur())),
+                    BoolOp::Nand => lhs.nand(meta.query_advice(advice[1], Rotation::cur())),
+                    BoolOp::Not => lhs.not(),
+                };
+                vec![s * (poly - out)]
+            });
+        }
+
+        config
+    }
+
+    /// Assigns a bitwise `op` region with `out` as the claimed result. The
+    /// instructions compute `out` honestly; tests use this directly to check
+    /// that a tampered result is rejected.
+    pub fn assign_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: BoolOp,
+        a: &[Bit<F>],
+        b: &[Bit<F>],
+        out: Vec<Option<F>>,
+    ) -> Result<Vec<Bit<F>>, Error> {
+        let config = self.config();
+        if out.len() != a.len() || (op != BoolOp::Not && b.len() != a.len()) {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || format!("{:?}", op),
+            |mut region: Region<'_, F>| {
+                a.iter()
+                    .zip(out.iter())
+                    .enumerate()
+                    .map(|(i, (a, out))| {
+                        config.selector(op).enable(&mut region, i)?;
+                        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], i)?;
+                        if op != BoolOp::Not {
+                            b[i].0.copy_advice(|| "rhs", &mut region, config.advice[1], i)?;
+                        }
+                        region
+                            .assign_advice(|| "out", config.advice[2], i, || out.ok_or(Error::Synthesis))
+                            .map(Bit)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn binary_op(&self, layouter: impl Layouter<F>, op: BoolOp, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error> {
+        let mut out = self.bitwise(layouter, op, &[a.clone()], &[b.clone()])?;
+        Ok(out.remove(0))
+    }
+}
+
+impl<F: FieldExt> BooleanInstructions<F> for BooleanChip<F> {
+    fn assign_bit(&self, layouter: impl Layouter<F>, value: Option<F>) -> Result<Bit<F>, Error> {
+        let mut bits = self.assign_bits(layouter, &[value])?;
+        Ok(bits.remove(0))
+    }
+
+    fn assign_bits(&self, mut layouter: impl Layouter<F>, values: &[Option<F>]) -> Result<Vec<Bit<F>>, Error> {
+        let config = self.config();
+        // MockProver cannot locate failures next to an empty region.
+        if values.is_empty() {
+            return Ok(vec![]);
+        }
+        layouter.assign_region(
+            || "assign bits",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        config.s_bit.enable(&mut region, i)?;
+                        region
+                            .assign_advice(|| format!("bit {}", i), config.advice[0], i, || value.ok_or(Error::Synthesis))
+                            .map(Bit)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn and(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error> {
+        self.binary_op(layouter, BoolOp::And, a, b)
+    }
+
+    fn or(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error> {
+        self.binary_op(layouter, BoolOp::Or, a, b)
+    }
+
+    fn xor(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error> {
+        self.binary_op(layouter, BoolOp::Xor, a, b)
+    }
+
+    fn nand(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error> {
+        self.binary_op(layouter, BoolOp::Nand, a, b)
+    }
+
+    fn not(&self, layouter: impl Layouter<F>, a: &Bit<F>) -> Result<Bit<F>, Error> {
+        let mut out = self.bitwise(layouter, BoolOp::Not, &[a.clone()], &[])?;
+        Ok(out.remove(0))
+    }
+
+    fn bitwise(
+        &self,
+        layouter: impl Layouter<F>,
+        op: BoolOp,
+        a: &[Bit<F>],
+        b: &[Bit<F>],
+    ) -> Result<Vec<Bit<F>>, Error> {
+        let bit = |cell: Option<&Bit<F>>| cell.and_then(|c| c.0.value()).map(|v| *v == F::one());
+        let out = (0..a.len())
+            .map(|i| {
+                let lhs = bit(a.get(i));
+                let rhs = if op == BoolOp::Not { Some(false) } else { bit(b.get(i)) };
+                lhs.zip(rhs).map(|(lhs, rhs)| F::from(op.eval(lhs, rhs) as u64))
+            })
+            .collect();
+        self.assign_op(layouter, op, a, b, out)
+    }
+
+    fn expose_public(&self, mut layouter: impl Layouter<F>, bit: &Bit<F>, row: usize) -> Result<(), Error> {
+        layouter.constrain_instance(bit.0.cell(), self.config.instance, row)
+    }
+}
@@ -0,0 +1,343 @@
+//! Runs the repo's circuits from the command line, with inputs and outputs
+//! on disk:
+//!
+//! ```text
+//! cli setup --k K --params FILE [--ptau FILE]
+//! cli keygen CIRCUIT --k K --params FILE --private FILE --vk FILE
+//! cli prove CIRCUIT --k K --params FILE --vk FILE --private FILE --public FILE --proof FILE
+//! cli verify CIRCUIT --k K --params FILE --vk FILE --public FILE --proof FILE
+//! cli mock CIRCUIT --k K --private FILE [--public FILE]
+//! ```
+//!
+//! `prove` and `verify` also take `--transcript blake2b` (the default) or
+//! `--transcript keccak256`, for proofs checked on the EVM.
+//!
+//! Given `--envelope FILE` instead of `--public` and `--proof`, `prove`
+//! writes the proof and public inputs as one envelope (`envelope.rs`), JSON
+//! if `FILE` ends in `.json` and binary otherwise, and `verify` reads one,
+//! refusing it if it was made for another circuit, `k` or verifying key.
+//!
+//! Private inputs follow the schemas in `inputs.rs`. `prove` writes the
+//! public inputs it derives from them to `--public`, for `verify` to read;
+//! `mock` derives them too unless `--public` overrides them.
+//!
+//! Exits with 0 on success, 1 if a proof or mock run is rejected, and 2 on
+//! any other error.
+
+mod inputs;
+
+// The circuits are those of the example binaries, so their files are shared
+// as modules rather than copied. Their `main`s go unused here; the chips and
+// the prover they use come from the library, so there is one copy of each.
+#[allow(dead_code)]
+#[path = "example1.rs"]
+mod fibo;
+#[allow(dead_code)]
+#[path = "example2.rs"]
+mod fibo_rows;
+#[allow(dead_code)]
+#[path = "example3.rs"]
+mod fibo_xor;
+#[allow(dead_code)]
+#[path = "multiplexer.rs"]
+mod select;
+#[allow(dead_code)]
+#[path = "sort.rs"]
+mod sort;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use halo2_proofs::{
+    dev::MockProver,
+    pairing::bn256::{Fr, G1Affine},
+    plonk::Error,
+    poly::commitment::Params,
+};
+use halo2_tutorial::envelope::{Envelope, EnvelopeError};
+use halo2_tutorial::prover::{self, TranscriptKind};
+use halo2_tutorial::{keys, srs};
+
+use inputs::{
+    FiboInputs, FiboRowsInputs, FiboXorInputs, InputError, Inputs, RangeTableInputs, SelectInputs, SortInputs,
+};
+
+const USAGE: &str = "usage: cli (setup | keygen | prove | verify | mock) [CIRCUIT] --name value ...
+circuits: fibo, fibo-rows, fibo-xor, sort, range-table, select";
+
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Io(io::Error),
+    Input(InputError),
+    Key(keys::KeyError),
+    Envelope(EnvelopeError),
+    Srs(srs::SrsError),
+    Synthesis(Error),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(msg) => write!(f, "{}\n{}", msg, USAGE),
+            CliError::Io(err) => write!(f, "i/o error: {}", err),
+            CliError::Input(err) => write!(f, "bad inputs: {}", err),
+            CliError::Key(err) => write!(f, "key file: {}", err),
+            CliError::Envelope(err) => write!(f, "{}", err),
+            CliError::Srs(err) => write!(f, "{}", err),
+            CliError::Synthesis(err) => write!(f, "synthesis failed: {:?}", err),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        CliError::Io(err)
+    }
+}
+
+impl From<InputError> for CliError {
+    fn from(err: InputError) -> Self {
+        CliError::Input(err)
+    }
+}
+
+impl From<keys::KeyError> for CliError {
+    fn from(err: keys::KeyError) -> Self {
+        CliError::Key(err)
+    }
+}
+
+impl From<EnvelopeError> for CliError {
+    fn from(err: EnvelopeError) -> Self {
+        CliError::Envelope(err)
+    }
+}
+
+impl From<srs::SrsError> for CliError {
+    fn from(err: srs::SrsError) -> Self {
+        CliError::Srs(err)
+    }
+}
+
+impl From<Error> for CliError {
+    fn from(err: Error) -> Self {
+        CliError::Synthesis(err)
+    }
+}
+
+/// A subcommand, the circuit it applies to and its `--name value` options.
+struct Args {
+    command: String,
+    circuit: Option<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, CliError> {
+        let command = args.next().ok_or_else(|| CliError::Usage("missing command".to_string()))?;
+        let mut circuit = None;
+        let mut options = HashMap::new();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args.next().ok_or_else(|| CliError::Usage(format!("--{} needs a value", name)))?;
+                    options.insert(name.to_string(), value);
+                }
+                None if circuit.is_none() && command != "setup" => circuit = Some(arg),
+                None => return Err(CliError::Usage(format!("unexpected argument {}", arg))),
+            }
+        }
+        Ok(Self {
+            command,
+            circuit,
+            options,
+        })
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, CliError> {
+        self.options
+            .get(name)
+            .map(PathBuf::from)
+            .ok_or_else(|| CliError::Usage(format!("missing --{}", name)))
+    }
+
+    /// The transcript named by `--transcript`, Blake2b if there is none.
+    fn transcript(&self) -> Result<TranscriptKind, CliError> {
+        match self.options.get("transcript") {
+            None => Ok(TranscriptKind::default()),
+            Some(name) => {
+                TranscriptKind::from_name(name).ok_or_else(|| CliError::Usage(format!("unknown transcript {}", name)))
+            }
+        }
+    }
+
+    fn k(&self) -> Result<u32, CliError> {
+        let k = self.options.get("k").ok_or_else(|| CliError::Usage("missing --k".to_string()))?;
+        k.parse().map_err(|_| CliError::Usage(format!("bad --k {}", k)))
+    }
+}
+
+/// Reads the circuit and the public inputs derived for it from `--private`.
+fn read_private<I: Inputs>(args: &Args) -> Result<(I::Circuit, Vec<Vec<Fr>>), CliError> {
+    Ok(inputs::from_json::<I>(&fs::read(args.path("private")?)?)?)
+}
+
+fn read_instances(args: &Args) -> Result<Vec<Vec<Fr>>, CliError> {
+    Ok(inputs::instances_from_json(&fs::read(args.path("public")?)?)?)
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "json")
+}
+
+fn write_envelope(path: &Path, envelope: &Envelope) -> Result<(), CliError> {
+    if is_json(path) {
+        fs::write(path, envelope.to_json()?)?;
+    } else {
+        let mut bytes = vec![];
+        envelope.write(&mut bytes)?;
+        fs::write(path, bytes)?;
+    }
+    Ok(())
+}
+
+fn read_envelope(path: &Path) -> Result<Envelope, CliError> {
+    let bytes = fs::read(path)?;
+    if is_json(path) {
+        Ok(Envelope::from_json(&bytes)?)
+    } else {
+        Ok(Envelope::read(&mut bytes.as_slice())?)
+    }
+}
+
+fn public_inputs_size(instances: &[Vec<Fr>]) -> usize {
+    instances.iter().map(|column| column.len()).max().unwrap_or(0)
+}
+
+fn setup(args: &Args) -> Result<bool, CliError> {
+    let k = args.k()?;
+    let params = match args.options.get("ptau") {
+        Some(ptau) => srs::load(ptau.as_ref(), k)?,
+        None => {
+            eprintln!("warning: no --ptau given, generating insecure parameters");
+            Params::<G1Affine>::unsafe_setup::<halo2_proofs::pairing::bn256::Bn256>(k)
+        }
+    };
+    keys::write_params(&args.path("params")?, &params)?;
+    Ok(true)
+}
+
+/// Runs `args.command` on the circuit read from private inputs `I`, which
+/// `verify` never reads.
+fn run<I: Inputs>(args: &Args) -> Result<bool, CliError> {
+    let k = args.k()?;
+    match args.command.as_str() {
+        "keygen" => {
+            let (circuit, _) = read_private::<I>(args)?;
+            let params = keys::read_params(&args.path("params")?, k)?;
+            let pk = prover::keygen(&params, &circuit)?;
+            keys::write_vk::<I::Circuit>(&args.path("vk")?, k, pk.get_vk())?;
+            Ok(true)
+        }
+        "prove" => {
+            let (circuit, instances) = read_private::<I>(args)?;
+            let params = keys::read_params(&args.path("params")?, k)?;
+            let vk = keys::read_vk::<I::Circuit>(&args.path("vk")?, &params)?;
+            let pk = keys::regenerate_pk(&params, vk, &circuit)?;
+            let transcript = args.transcript()?;
+            let proof = prover::prove(&params, &pk, &circuit, &instances, transcript)?;
+            println!("proof size is {} bytes", proof.len());
+            if args.options.contains_key("envelope") {
+                let name = args.circuit.as_deref().expect("checked by dispatch");
+                let envelope = Envelope::new(name, k, pk.get_vk(), transcript, instances, proof)?;
+                write_envelope(&args.path("envelope")?, &envelope)?;
+            } else {
+                fs::write(args.path("proof")?, &proof)?;
+                fs::write(args.path("public")?, inputs::instances_to_json(&instances)?)?;
+            }
+            Ok(true)
+        }
+        "verify" if args.options.contains_key("envelope") => {
+            let envelope = read_envelope(&args.path("envelope")?)?;
+            let params = keys::read_params(&args.path("params")?, k)?;
+            let params_verifier = params.verifier(public_inputs_size(&envelope.instances))?;
+            let vk = keys::read_vk::<I::Circuit>(&args.path("vk")?, &params)?;
+            let name = args.circuit.as_deref().expect("checked by dispatch");
+            match envelope.verify(&params_verifier, name, k, &vk) {
+                Ok(()) => Ok(true),
+                Err(EnvelopeError::Rejected(err)) => {
+                    println!("proof rejected: {:?}", err);
+                    Ok(false)
+                }
+                Err(err) => Err(err.into()),
+            }
+        }
+        "verify" => {
+            let instances = read_instances(args)?;
+            let params = keys::read_params(&args.path("params")?, k)?;
+            let params_verifier = params.verifier(public_inputs_size(&instances))?;
+            let vk = keys::read_vk::<I::Circuit>(&args.path("vk")?, &params)?;
+            let proof = fs::read(args.path("proof")?)?;
+            match prover::verify(&params_verifier, &vk, &instances, &proof, args.transcript()?) {
+                Ok(()) => Ok(true),
+                Err(err) => {
+                    println!("proof rejected: {:?}", err);
+                    Ok(false)
+                }
+            }
+        }
+        "mock" => {
+            let (circuit, mut instances) = read_private::<I>(args)?;
+            if args.options.contains_key("public") {
+                instances = read_instances(args)?;
+            }
+            let prover = MockProver::run(k, &circuit, instances)?;
+            match prover.verify() {
+                Ok(()) => Ok(true),
+                Err(failures) => {
+                    for failure in failures {
+                        println!("{:?}", failure);
+                    }
+                    Ok(false)
+                }
+            }
+        }
+        _ => unreachable!("checked by dispatch"),
+    }
+}
+
+fn dispatch(args: &Args) -> Result<bool, CliError> {
+    match args.command.as_str() {
+        "setup" => return setup(args),
+        "keygen" | "prove" | "verify" | "mock" => {}
+        command => return Err(CliError::Usage(format!("unknown command {}", command))),
+    }
+
+    let name = args.circuit.as_deref().ok_or_else(|| CliError::Usage("missing circuit".to_string()))?;
+    match name {
+        "fibo" => run::<FiboInputs>(args),
+        "fibo-rows" => run::<FiboRowsInputs>(args),
+        "fibo-xor" => run::<FiboXorInputs>(args),
+        "sort" => run::<SortInputs>(args),
+        "range-table" => run::<RangeTableInputs>(args),
+        "select" => run::<SelectInputs>(args),
+        name => Err(CliError::Usage(format!("unknown circuit {}", name))),
+    }
+}
+
+fn main() {
+    let result = Args::parse(std::env::args().skip(1)).and_then(|args| dispatch(&args));
+    process::exit(match result {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            2
+        }
+    });
+}
@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::fmt;
+
+use halo2_proofs::{arithmetic::FieldExt, plonk::Error};
+
+/// A field element that does not fit the integer type a witness generator
+/// asked for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionError {
+    /// The cell the value was read from.
+    pub name: String,
+    /// The little-endian bytes of the value.
+    pub value: Vec<u8>,
+    /// The number of bytes the value had to fit in.
+    pub n_bytes: usize,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex: String = self.value.iter().rev().map(|b| format!("{:02x}", b)).collect();
+        write!(f, "{}: 0x{} does not fit in {} bytes", self.name, hex, self.n_bytes)
+    }
+}
+
+thread_local! {
+    static LAST: RefCell<Option<ConversionError>> = RefCell::new(None);
+}
+
+/// Fails synthesis because of `err`. `Error` has no room for the cell name
+/// and value, so `err` is kept for `take_last` on this thread.
+pub fn synthesis_error(err: ConversionError) -> Error {
+    LAST.with(|last| *last.borrow_mut() = Some(err));
+    Error::Synthesis
+}
+
+/// The conversion that last failed synthesis on this thread, if it has not
+/// been taken yet.
+pub fn take_last() -> Option<ConversionError> {
+    LAST.with(|last| last.borrow_mut().take())
+}
+
+/// Returns the lowest `n_bytes` little-endian bytes of `value`, or an error
+/// naming the cell `name` if any higher byte is set.
+pub fn to_bytes<F: FieldExt>(value: &F, n_bytes: usize, name: &str) -> Result<Vec<u8>, ConversionError> {
+    let repr = value.to_repr();
+    let bytes = repr.as_ref();
+    if bytes.iter().skip(n_bytes).any(|b| *b != 0) {
+        return Err(ConversionError {
+            name: name.to_string(),
+            value: bytes.to_vec(),
+            n_bytes,
+        });
+    }
+    let mut out = bytes[..n_bytes.min(bytes.len())].to_vec();
+    out.resize(n_bytes, 0);
+    Ok(out)
+}
+
+/// Converts `value` to a `u64`, or returns an error naming the cell `name`.
+pub fn to_u64<F: FieldExt>(value: &F, name: &str) -> Result<u64, ConversionError> {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&to_bytes(value, 8, name)?);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Converts `value` to a `u128`, or returns an error naming the cell `name`.
+pub fn to_u128<F: FieldExt>(value: &F, name: &str) -> Result<u128, ConversionError> {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&to_bytes(value, 16, name)?);
+    Ok(u128::from_le_bytes(bytes))
+}
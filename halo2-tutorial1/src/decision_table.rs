@@ -0,0 +1,417 @@
+use halo2_tutorial::{expr, field_chip, lt, mux, prover, table};
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+
+use field_chip::{FieldChip, FieldConfig, NumericInstruction, Number};
+use lt::{LtChip, LtConfig, LtInstruction};
+use mux::{MuxChip, MuxConfig, MuxInstructions};
+use table::RangTableConfig;
+
+/// A comparison of the input `x` against a constant bound.
+#[derive(Clone, Copy, Debug)]
+enum Guard {
+    /// `x < bound`
+    Lt(u64),
+    /// `x >= bound`
+    Ge(u64),
+}
+
+impl Guard {
+    fn bound(&self) -> u64 {
+        match self {
+            Guard::Lt(bound) | Guard::Ge(bound) => *bound,
+        }
+    }
+
+    fn holds(&self, x: u64) -> bool {
+        match self {
+            Guard::Lt(bound) => x < *bound,
+            Guard::Ge(bound) => x >= *bound,
+        }
+    }
+}
+
+/// A branch fires when all its guards hold, and then outputs
+/// `slope * x + intercept`. A branch without guards always fires.
+#[derive(Clone, Debug)]
+struct Branch {
+    guards: Vec<Guard>,
+    slope: u64,
+    intercept: i64,
+}
+
+impl Branch {
+    fn new(guards: &[Guard], slope: u64, intercept: i64) -> Self {
+        Self {
+            guards: guards.to_vec(),
+            slope,
+            intercept,
+        }
+    }
+}
+
+/// A decision table over a 64-bit input. The circuit only accepts inputs for
+/// which exactly one branch fires.
+#[derive(Clone, Debug)]
+struct DecisionTable(Vec<Branch>);
+
+impl DecisionTable {
+    /// Native evaluation. Returns the index and output of the branch that
+    /// fires, or `None` unless exactly one does.
+    fn eval<F: FieldExt>(&self, x: u64) -> Option<(usize, F)> {
+        let mut fired = self
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, branch)| branch.guards.iter().all(|g| g.holds(x)));
+
+        match (fired.next(), fired.next()) {
+            (Some((i, branch)), None) => Some((i, F::from(branch.slope) * F::from(x) + from_i64::<F>(branch.intercept))),
+            _ => None,
+        }
+    }
+}
+
+fn from_i64<F: FieldExt>(v: i64) -> F {
+    F::from(v.unsigned_abs()) * if v < 0 { -F::one() } else { F::one() }
+}
+
+trait DecisionInstructions<F: FieldExt>: Chip<F> {
+    /// Returns the output of the single branch of `table` that fires on `x`.
+    fn evaluate(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &Number<F>,
+        table: &DecisionTable,
+    ) -> Result<Number<F>, Error>;
+}
+
+#[derive(Clone, Debug)]
+struct DecisionConfig<F: FieldExt> {
+    field: FieldConfig,
+    mux: MuxConfig,
+    /// The input, copied onto every guard row.
+    x: Column<Advice>,
+    /// The guard bound, loaded from a constant.
+    bound: Column<Advice>,
+    /// 1 on rows whose guard is `x >= bound`.
+    negate: Column<Fixed>,
+    /// The guard outcome, `lt` or `1 - lt`.
+    guard: Column<Advice>,
+    s_guard: Selector,
+    s_range: Selector,
+    lt: LtConfig<F, 8>,
+    table: RangTableConfig<F>,
+}
+
+struct DecisionChip<F: FieldExt> {
+    config: DecisionConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for DecisionChip<F> {
+    type Config = DecisionConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> DecisionChip<F> {
+    fn construct(config: DecisionConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> DecisionConfig<F> {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        let field = FieldChip::configure(meta, [advice[0], advice[1]], instance, constant);
+        let mux = MuxChip::configure(meta, advice, instance, constant);
+
+        let x = meta.advice_column();
+        let bound = meta.advice_column();
+        let guard = meta.advice_column();
+        let negate = meta.fixed_column();
+        let s_guard = meta.complex_selector();
+        let s_range = meta.complex_selector();
+        let table = RangTableConfig::configure(meta);
+
+        for col in [x, bound, guard] {
+            meta.enable_equality(col);
+        }
+
+        let lt = LtChip::configure(
+            meta,
+            |meta| meta.query_selector(s_guard),
+            |meta| meta.query_advice(x, Rotation::cur()),
+            |meta| meta.query_advice(bound, Rotation::cur()),
+        );
+
+        // |  x  | bound | lt, diff | guard | negate | s_guard | s_range |
+        // |-----|-------|----------|-------|--------|---------|---------|
+        // |  x  |       | -, bytes |       |        |    0    |    1    |
+        // |  x  |  b_0  | x < b_0  |  g_0  |  0/1   |    1    |    0    |
+        // | ... |  ...  |   ...    |  ...  |  ...   |   ...   |   ...   |
+        //
+        // The first row range checks `x` through the diff columns, which the
+        // comparisons need to be sound.
+        meta.create_gate("guard", |meta| {
+            let s = meta.query_selector(s_guard);
+            let lt = lt.is_lt(meta, None);
+            let negate = meta.query_fixed(negate, Rotation::cur());
+            let guard = meta.query_advice(guard, Rotation::cur());
+            vec![s * (expr::select(negate, expr::not(lt.clone()), lt) - guard)]
+        });
+
+        meta.create_gate("range", |meta| {
+            let s = meta.query_selector(s_range);
+            let x = meta.query_advice(x, Rotation::cur());
+            let bytes = lt
+                .diff
+                .iter()
+                .map(|c| meta.query_advice(*c, Rotation::cur()))
+                .collect::<Vec<Expression<F>>>();
+            vec![s * (x - expr::expr_from_bytes(&bytes))]
+        });
+
+        for byte in lt.diff {
+            meta.lookup("byte", |meta| {
+                let s = meta.query_selector(s_guard) + meta.query_selector(s_range);
+                vec![(s * meta.query_advice(byte, Rotation::cur()), table.col_value)]
+            });
+        }
+
+        DecisionConfig {
+            field,
+            mux,
+            x,
+            bound,
+            negate,
+            guard,
+            s_guard,
+            s_range,
+            lt,
+            table,
+        }
+    }
+
+    fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.config.table.load(&mut layouter, (0..256).collect())
+    }
+
+    /// Range checks `x` and evaluates `guards` on it, one row each.
+    fn assign_guards(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &Number<F>,
+        guards: &[Guard],
+    ) -> Result<Vec<Number<F>>, Error> {
+        let config = self.config();
+        let lt_chip = LtChip::construct(config.lt);
+        let x_value = x.0.value().copied();
+
+        layouter.assign_region(
+            || "guards",
+            |mut region: Region<'_, F>| {
+                config.s_range.enable(&mut region, 0)?;
+                x.0.copy_advice(|| "x", &mut region, config.x, 0)?;
+                let repr = x_value.map(|x| x.to_repr());
+                for (idx, column) in config.lt.diff.iter().enumerate() {
+                    region.assign_advice(
+                        || format!("x byte {}", idx),
+                        *column,
+                        0,
+                        || {
+                            repr.as_ref()
+                                .map(|repr| F::from(repr.as_ref()[idx] as u64))
+                                .ok_or(Error::Synthesis)
+                        },
+                    )?;
+                }
+
+                guards
+                    .iter()
+                    .enumerate()
+                    .map(|(i, guard)| {
+                        let row = i + 1;
+                        let bound = F::from(guard.bound());
+                        let negate = matches!(guard, Guard::Ge(_));
+                        config.s_guard.enable(&mut region, row)?;
+
+                        x.0.copy_advice(|| "x", &mut region, config.x, row)?;
+                        region.assign_advice_from_constant(|| "bound", config.bound, row, bound)?;
+                        region.assign_fixed(|| "negate", config.negate, row, || Ok(F::from(negate as u64)))?;
+                        lt_chip.assign(&mut region, row, x_value, Some(bound))?;
+
+                        let holds = x_value.map(|x| (x < bound) != negate);
+                        region
+                            .assign_advice(
+                                || format!("{:?}", guard),
+                                config.guard,
+                                row,
+                                || holds.map(|holds| F::from(holds as u64)).ok_or(Error::Synthesis),
+                            )
+                            .map(Number)
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> DecisionInstructions<F> for DecisionChip<F> {
+    fn evaluate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &Number<F>,
+        table: &DecisionTable,
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+        let field_chip = FieldChip::construct(config.field.clone());
+        let mux_chip = MuxChip::construct(config.mux.clone());
+
+        let mut fired = vec![];
+        let mut outputs = vec![];
+        for (i, branch) in table.0.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("branch {}", i));
+
+            // fired = g_0 * g_1 * ... * g_n, or 1 without guards.
+            let guards = self.assign_guards(layouter.namespace(|| "guards"), x, &branch.guards)?;
+            let mut guards = guards.into_iter();
+            let mut f = match guards.next() {
+                Some(g) => g,
+                None => field_chip.load_constant(layouter.namespace(|| "always"), F::one())?,
+            };
+            for g in guards {
+                f = field_chip.mul(layouter.namespace(|| "and"), f, g)?;
+            }
+            fired.push(f.0);
+
+            let slope = field_chip.load_constant(layouter.namespace(|| "slope"), F::from(branch.slope))?;
+            let intercept = field_chip.load_constant(layouter.namespace(|| "intercept"), from_i64(branch.intercept))?;
+            let out = field_chip.mul(layouter.namespace(|| "slope * x"), slope, x.clone())?;
+            let out = field_chip.add(layouter.namespace(|| "+ intercept"), out, intercept)?;
+            outputs.push(out.0);
+        }
+
+        // The one-hot check proves exactly one branch fired.
+        mux_chip
+            .select_one_hot(layouter.namespace(|| "select branch"), &fired, &outputs)
+            .map(Number)
+    }
+}
+
+/// Proves `y = table(x)` with `y` exposed on instance row 0.
+struct DecisionCircuit<F> {
+    x: Option<u64>,
+    table: DecisionTable,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for DecisionCircuit<F> {
+    type Config = DecisionConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            x: None,
+            table: self.table.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        DecisionChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = DecisionChip::construct(config);
+        chip.load_table(layouter.namespace(|| "byte table"))?;
+
+        let field_chip = FieldChip::construct(chip.config().field.clone());
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x.map(F::from))?;
+        let y = chip.evaluate(layouter.namespace(|| "table(x)"), &x, &self.table)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose y"), y, 0)
+    }
+}
+
+fn main() {
+    let k = 9;
+
+    // Tax brackets: 0 below 1000, 2 per unit from 1000, 3 per unit from 5000.
+    let tax = DecisionTable(vec![
+        Branch::new(&[Guard::Lt(1000)], 0, 0),
+        Branch::new(&[Guard::Ge(1000), Guard::Lt(5000)], 2, -2000),
+        Branch::new(&[Guard::Ge(5000)], 3, -7000),
+    ]);
+
+    // Fee tiers with a catch-all: a flat fee for small amounts, a nested
+    // discount band, and a cap. The catch-all is excluded by its own guards.
+    let fee = DecisionTable(vec![
+        Branch::new(&[Guard::Lt(100)], 0, 5),
+        Branch::new(&[Guard::Ge(100), Guard::Lt(500), Guard::Lt(250)], 1, 0),
+        Branch::new(&[Guard::Ge(250), Guard::Lt(500)], 1, -25),
+        Branch::new(&[Guard::Ge(500)], 0, 475),
+    ]);
+
+    for (table, xs) in [(&tax, vec![0, 999, 1000, 4999, 5000, 12345]), (&fee, vec![0, 99, 100, 249, 250, 499, 500, u64::MAX])] {
+        for x in xs {
+            let (_, y) = table.eval::<Fp>(x).expect("exactly one branch fires");
+            let circuit = DecisionCircuit::<Fp> { x: Some(x), table: table.clone(), _marker: PhantomData };
+
+            let prover = MockProver::run(k, &circuit, vec![vec![y]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "x = {}", x);
+
+            let prover = MockProver::run(k, &circuit, vec![vec![y + Fp::one()]]).unwrap();
+            assert!(prover.verify().is_err(), "x = {}", x);
+        }
+    }
+    assert_eq!(tax.eval::<Fp>(6000), Some((2, Fp::from(11000))));
+
+    let circuit = DecisionCircuit::<Fp> { x: Some(6000), table: tax.clone(), _marker: PhantomData };
+    println!("{}", prover::round_trip(k, &circuit, &[vec![Fp::from(11000)]]).unwrap());
+
+    // Overlapping branches: both fire below 100.
+    let overlap = DecisionTable(vec![
+        Branch::new(&[Guard::Lt(100)], 0, 1),
+        Branch::new(&[Guard::Lt(200)], 0, 2),
+        Branch::new(&[Guard::Ge(200)], 0, 3),
+    ]);
+    // A gap: nothing fires between 100 and 200.
+    let gap = DecisionTable(vec![
+        Branch::new(&[Guard::Lt(100)], 0, 1),
+        Branch::new(&[Guard::Ge(200)], 0, 3),
+    ]);
+
+    assert_eq!(overlap.eval::<Fp>(150), Some((1, Fp::from(2))));
+    let circuit = DecisionCircuit::<Fp> { x: Some(150), table: overlap.clone(), _marker: PhantomData };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(2)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    for (table, x, y) in [(&overlap, 50, 1), (&overlap, 50, 3), (&gap, 150, 0)] {
+        assert_eq!(table.eval::<Fp>(x), None);
+        let circuit = DecisionCircuit::<Fp> { x: Some(x), table: table.clone(), _marker: PhantomData };
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(y)]]).unwrap();
+        assert!(prover.verify().is_err(), "x = {}", x);
+    }
+}
@@ -0,0 +1,395 @@
+use halo2_tutorial::prover;
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+trait DotProductInstructions<F: FieldExt>: Chip<F> {
+    /// Loads a vector into the circuit as private inputs.
+    fn load_private(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Option<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>;
+
+    /// Loads a vector from `len` consecutive rows of the instance column,
+    /// starting at `start`.
+    fn load_public(
+        &self,
+        layouter: impl Layouter<F>,
+        start: usize,
+        len: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>;
+
+    /// Returns `c = Σ a_i * b_i`.
+    fn dot(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[AssignedCell<F, F>],
+        b: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Returns `c = M * v`, with one inner product per row of `M`.
+    fn mat_vec(
+        &self,
+        layouter: impl Layouter<F>,
+        m: &[Vec<AssignedCell<F, F>>],
+        v: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+struct DotProductChip<F: FieldExt> {
+    config: DotProductConfig,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+struct DotProductConfig {
+    /// The two input vectors and the running sum.
+    advice: [Column<Advice>; 3],
+
+    /// This is the public input (instance) column.
+    instance: Column<Instance>,
+
+    /// Enables one accumulation step.
+    s_dot: Selector,
+}
+
+impl<F: FieldExt> DotProductChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> <Self as Chip<F>>::Config {
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+        let s_dot = meta.selector();
+
+        meta.create_gate("dot", |meta| {
+            // The running sum starts at zero on the first row of the region and
+            // picks up one product per row:
+            //
+            // |  a0 |  a1 |    a2   | s_dot |
+            // |-----|-----|---------|-------|
+            // |     |     |  acc    |       |
+            // | a_i | b_i |  acc'   |   1   |
+            //
+            // acc' = acc + a_i * b_i
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let acc_prev = meta.query_advice(advice[2], Rotation::prev());
+            let acc = meta.query_advice(advice[2], Rotation::cur());
+            let s_dot = meta.query_selector(s_dot);
+
+            vec![s_dot * (acc_prev + a * b - acc)]
+        });
+
+        DotProductConfig {
+            advice,
+            instance,
+            s_dot,
+        }
+    }
+}
+
+impl<F: FieldExt> Chip<F> for DotProductChip<F> {
+    type Config = DotProductConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> DotProductInstructions<F> for DotProductChip<F> {
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Option<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        region.assign_advice(|| "private input", config.advice[0], i, || value.ok_or(Error::Synthesis))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn load_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        start: usize,
+        len: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load public",
+            |mut region| {
+                (0..len)
+                    .map(|i| {
+                        region.assign_advice_from_instance(
+                            || "public input",
+                            config.instance,
+                            start + i,
+                            config.advice[0],
+                            i,
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn dot(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[AssignedCell<F, F>],
+        b: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        if a.len() != b.len() {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "dot",
+            |mut region: Region<'_, F>| {
+                let mut acc =
+                    region.assign_advice_from_constant(|| "acc", config.advice[2], 0, F::zero())?;
+
+                for (i, (a, b)) in a.iter().zip(b.iter()).enumerate() {
+                    let row = i + 1;
+                    config.s_dot.enable(&mut region, row)?;
+
+                    a.copy_advice(|| "a", &mut region, config.advice[0], row)?;
+                    b.copy_advice(|| "b", &mut region, config.advice[1], row)?;
+
+                    let value = acc
+                        .value()
+                        .and_then(|acc| a.value().and_then(|a| b.value().map(|b| *acc + *a * *b)));
+                    acc = region.assign_advice(
+                        || "acc + a * b",
+                        config.advice[2],
+                        row,
+                        || value.ok_or(Error::Synthesis),
+                    )?;
+                }
+
+                Ok(acc)
+            },
+        )
+    }
+
+    fn mat_vec(
+        &self,
+        mut layouter: impl Layouter<F>,
+        m: &[Vec<AssignedCell<F, F>>],
+        v: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        m.iter()
+            .enumerate()
+            .map(|(i, row)| self.dot(layouter.namespace(|| format!("row {}", i)), row, v))
+            .collect()
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.constrain_instance(cell.cell(), config.instance, row)
+    }
+}
+
+/// Scores a private feature vector against public weights.
+///
+/// Instance layout: `weights[0..n]`, then the score.
+#[derive(Default)]
+struct ScoreCircuit<F: FieldExt> {
+    features: Vec<Option<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for ScoreCircuit<F> {
+    type Config = DotProductConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            features: vec![None; self.features.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        DotProductChip::configure(meta, advice, instance, constant)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = DotProductChip::<F>::construct(config);
+        let n = self.features.len();
+
+        let features = chip.load_private(layouter.namespace(|| "load features"), &self.features)?;
+        let weights = chip.load_public(layouter.namespace(|| "load weights"), 0, n)?;
+
+        let score = chip.dot(layouter.namespace(|| "features . weights"), &features, &weights)?;
+        chip.expose_public(layouter.namespace(|| "expose score"), &score, n)
+    }
+}
+
+/// Proves `M * v` for a private matrix `M` and a public vector `v`.
+///
+/// Instance layout: `v[0..cols]`, then the `rows` outputs.
+#[derive(Default)]
+struct MatVecCircuit<F: FieldExt> {
+    matrix: Vec<Vec<Option<F>>>,
+}
+
+impl<F: FieldExt> Circuit<F> for MatVecCircuit<F> {
+    type Config = DotProductConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            matrix: self.matrix.iter().map(|row| vec![None; row.len()]).collect(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        ScoreCircuit::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = DotProductChip::<F>::construct(config);
+        let cols = self.matrix.first().map_or(0, |row| row.len());
+
+        let matrix = self
+            .matrix
+            .iter()
+            .enumerate()
+            .map(|(i, row)| chip.load_private(layouter.namespace(|| format!("load row {}", i)), row))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let v = chip.load_public(layouter.namespace(|| "load v"), 0, cols)?;
+
+        let out = chip.mat_vec(layouter.namespace(|| "M * v"), &matrix, &v)?;
+        for (i, cell) in out.iter().enumerate() {
+            chip.expose_public(layouter.namespace(|| "expose output"), cell, cols + i)?;
+        }
+        Ok(())
+    }
+}
+
+fn dot(a: &[u64], b: &[u64]) -> u64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn score_test() {
+    let k = 5;
+
+    let features = [3u64, 0, 7, 2, 5];
+    let weights = [4u64, 9, 1, 6, 2];
+    let score = dot(&features, &weights);
+
+    let circuit = ScoreCircuit {
+        features: features.iter().map(|f| Some(Fp::from(*f))).collect(),
+    };
+
+    let mut public_inputs: Vec<Fp> = weights.iter().map(|w| Fp::from(*w)).collect();
+    public_inputs.push(Fp::from(score));
+
+    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    println!("{}", prover::round_trip(k, &circuit, &[public_inputs.clone()]).unwrap());
+
+    // A different score is rejected.
+    let mut bad_inputs = public_inputs.clone();
+    bad_inputs[features.len()] += Fp::one();
+    let prover = MockProver::run(k, &circuit, vec![bad_inputs]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // So is the same score against different weights.
+    let mut bad_inputs = public_inputs;
+    bad_inputs[0] += Fp::one();
+    let prover = MockProver::run(k, &circuit, vec![bad_inputs]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+fn mat_vec_test() {
+    let k = 6;
+
+    let matrix = vec![vec![1u64, 2, 3, 4], vec![0, 5, 0, 6], vec![7, 8, 9, 10]];
+    let v = [2u64, 3, 5, 7];
+    let out: Vec<u64> = matrix.iter().map(|row| dot(row, &v)).collect();
+
+    let circuit = MatVecCircuit {
+        matrix: matrix
+            .iter()
+            .map(|row| row.iter().map(|x| Some(Fp::from(*x))).collect())
+            .collect(),
+    };
+
+    let mut public_inputs: Vec<Fp> = v.iter().map(|x| Fp::from(*x)).collect();
+    public_inputs.extend(out.iter().map(|x| Fp::from(*x)));
+
+    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Tampering with any single output is rejected.
+    for i in 0..out.len() {
+        let mut bad_inputs = public_inputs.clone();
+        bad_inputs[v.len() + i] += Fp::one();
+        let prover = MockProver::run(k, &circuit, vec![bad_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+fn main() {
+    println!("# Dot product.");
+    score_test();
+    println!("# Matrix-vector product.");
+    mat_vec_test();
+    println!("# Done.");
+}
@@ -0,0 +1,348 @@
+#![allow(dead_code)]
+
+//! A proof with what is needed to check it against the right key: the
+//! circuit it was made for, `k`, a hash of the verifying key, the transcript
+//! and the public inputs. It is written either as binary:
+//!
+//! ```text
+//! "H2PE" | version: u8 | circuit id: u16 length, utf-8 | k: u32 | vk hash: 32 bytes
+//!        | transcript: u8 | columns: u32 | per column, rows: u32 then 32 bytes per row
+//!        | proof: u32 length, bytes
+//! ```
+//!
+//! with integers little-endian and field elements in their canonical
+//! little-endian encoding, or as JSON, with field elements and hashes as `0x`
+//! big-endian hex like `inputs.rs` writes them.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use halo2_proofs::{
+    pairing::bn256::{Bn256, Fr, G1Affine},
+    pairing::group::ff::PrimeField,
+    plonk::{Error, VerifyingKey},
+    poly::commitment::ParamsVerifier,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::keccak::keccak256;
+use crate::prover::{self, TranscriptKind};
+
+/// Tags the binary envelopes written by `Envelope::write`.
+const MAGIC: &[u8; 4] = b"H2PE";
+
+/// The format version written, and the only one read.
+pub const VERSION: u8 = 1;
+
+/// Why an envelope could not be read, or was refused.
+#[derive(Debug)]
+pub enum EnvelopeError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The binary envelope does not start with the expected tag.
+    NotAnEnvelope,
+    /// The envelope was written by an unknown version of the format.
+    Version(u8),
+    /// A field of the envelope cannot be decoded.
+    Malformed(String),
+    /// The envelope holds a proof for another circuit.
+    WrongCircuit { found: String, expected: String },
+    /// The envelope holds a proof for circuits of `2^found` rows.
+    WrongK { found: u32, expected: u32 },
+    /// The proof was made with a different verifying key.
+    WrongVk,
+    /// The envelope matches the key but its proof does not verify.
+    Rejected(Error),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Io(err) => write!(f, "i/o error: {}", err),
+            EnvelopeError::Json(err) => write!(f, "bad json: {}", err),
+            EnvelopeError::NotAnEnvelope => write!(f, "not a proof envelope"),
+            EnvelopeError::Version(version) => {
+                write!(f, "envelope version {} is not supported, expected {}", version, VERSION)
+            }
+            EnvelopeError::Malformed(msg) => write!(f, "malformed envelope: {}", msg),
+            EnvelopeError::WrongCircuit { found, expected } => {
+                write!(f, "envelope is for circuit {}, expected {}", found, expected)
+            }
+            EnvelopeError::WrongK { found, expected } => write!(f, "envelope is for k = {}, expected k = {}", found, expected),
+            EnvelopeError::WrongVk => write!(f, "envelope was made with a different verifying key"),
+            EnvelopeError::Rejected(err) => write!(f, "proof rejected: {:?}", err),
+        }
+    }
+}
+
+impl From<io::Error> for EnvelopeError {
+    fn from(err: io::Error) -> Self {
+        EnvelopeError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for EnvelopeError {
+    fn from(err: serde_json::Error) -> Self {
+        EnvelopeError::Json(err)
+    }
+}
+
+fn malformed(msg: &str) -> EnvelopeError {
+    EnvelopeError::Malformed(msg.to_string())
+}
+
+/// The Keccak-256 hash of `vk` as `VerifyingKey::write` serializes it.
+pub fn vk_hash(vk: &VerifyingKey<G1Affine>) -> Result<[u8; 32], EnvelopeError> {
+    let mut bytes = vec![];
+    vk.write(&mut bytes)?;
+    Ok(keccak256(&bytes))
+}
+
+/// A proof and its public inputs, labelled with the key that checks them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope {
+    /// The circuit's name, as the `cli` binary calls it.
+    pub circuit: String,
+    pub k: u32,
+    pub vk_hash: [u8; 32],
+    pub transcript: TranscriptKind,
+    /// The public inputs, one vector per instance column.
+    pub instances: Vec<Vec<Fr>>,
+    pub proof: Vec<u8>,
+}
+
+impl Envelope {
+    pub fn new(
+        circuit: &str,
+        k: u32,
+        vk: &VerifyingKey<G1Affine>,
+        transcript: TranscriptKind,
+        instances: Vec<Vec<Fr>>,
+        proof: Vec<u8>,
+    ) -> Result<Self, EnvelopeError> {
+        Ok(Self {
+            circuit: circuit.to_string(),
+            k,
+            vk_hash: vk_hash(vk)?,
+            transcript,
+            instances,
+            proof,
+        })
+    }
+
+    /// Refuses the envelope unless it was made for `circuit` on `2^k` rows
+    /// with `vk`.
+    pub fn check(&self, circuit: &str, k: u32, vk: &VerifyingKey<G1Affine>) -> Result<(), EnvelopeError> {
+        if self.circuit != circuit {
+            return Err(EnvelopeError::WrongCircuit {
+                found: self.circuit.clone(),
+                expected: circuit.to_string(),
+            });
+        }
+        if self.k != k {
+            return Err(EnvelopeError::WrongK {
+                found: self.k,
+                expected: k,
+            });
+        }
+        if self.vk_hash != vk_hash(vk)? {
+            return Err(EnvelopeError::WrongVk);
+        }
+        Ok(())
+    }
+
+    /// Checks the envelope against `circuit`, `k` and `vk`, then verifies its
+    /// proof with the transcript it names.
+    pub fn verify(
+        &self,
+        params_verifier: &ParamsVerifier<Bn256>,
+        circuit: &str,
+        k: u32,
+        vk: &VerifyingKey<G1Affine>,
+    ) -> Result<(), EnvelopeError> {
+        self.check(circuit, k, vk)?;
+        prover::verify(params_verifier, vk, &self.instances, &self.proof, self.transcript)
+            .map_err(EnvelopeError::Rejected)
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), EnvelopeError> {
+        let len = |len: usize, what: &str| u32::try_from(len).map_err(|_| malformed(&format!("{} is too long", what)));
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        let circuit_len = u16::try_from(self.circuit.len()).map_err(|_| malformed("circuit id is too long"))?;
+        writer.write_all(&circuit_len.to_le_bytes())?;
+        writer.write_all(self.circuit.as_bytes())?;
+        writer.write_all(&self.k.to_le_bytes())?;
+        writer.write_all(&self.vk_hash)?;
+        writer.write_all(&[transcript_tag(self.transcript)])?;
+        writer.write_all(&len(self.instances.len(), "instances")?.to_le_bytes())?;
+        for column in &self.instances {
+            writer.write_all(&len(column.len(), "instance column")?.to_le_bytes())?;
+            for value in column {
+                writer.write_all(value.to_repr().as_ref())?;
+            }
+        }
+        writer.write_all(&len(self.proof.len(), "proof")?.to_le_bytes())?;
+        writer.write_all(&self.proof)?;
+        Ok(())
+    }
+
+    /// Reads an envelope written by `write`. Lengths are taken on trust only
+    /// as far as the bytes that follow them.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, EnvelopeError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(EnvelopeError::NotAnEnvelope);
+        }
+        let version = read_array::<_, 1>(reader)?[0];
+        if version != VERSION {
+            return Err(EnvelopeError::Version(version));
+        }
+
+        let circuit_len = u16::from_le_bytes(read_array(reader)?);
+        let circuit = String::from_utf8(read_bytes(reader, circuit_len as usize)?)
+            .map_err(|_| malformed("circuit id is not utf-8"))?;
+        let k = u32::from_le_bytes(read_array(reader)?);
+        let vk_hash = read_array(reader)?;
+        let transcript = transcript_from_tag(read_array::<_, 1>(reader)?[0])?;
+
+        let columns = u32::from_le_bytes(read_array(reader)?);
+        let mut instances = vec![];
+        for _ in 0..columns {
+            let rows = u32::from_le_bytes(read_array(reader)?);
+            let mut column = vec![];
+            for _ in 0..rows {
+                let mut repr = <Fr as PrimeField>::Repr::default();
+                reader.read_exact(repr.as_mut())?;
+                column.push(Option::from(Fr::from_repr(repr)).ok_or_else(|| malformed("public input is not a field element"))?);
+            }
+            instances.push(column);
+        }
+
+        let proof_len = u32::from_le_bytes(read_array(reader)?);
+        let proof = read_bytes(reader, proof_len as usize)?;
+        Ok(Self {
+            circuit,
+            k,
+            vk_hash,
+            transcript,
+            instances,
+            proof,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, EnvelopeError> {
+        let json = EnvelopeJson {
+            version: VERSION,
+            circuit: self.circuit.clone(),
+            k: self.k,
+            vk_hash: to_hex(self.vk_hash.iter().copied()),
+            transcript: self.transcript.name().to_string(),
+            instances: self
+                .instances
+                .iter()
+                .map(|column| column.iter().map(|value| to_hex(value.to_repr().as_ref().iter().rev().copied())).collect())
+                .collect(),
+            proof: to_hex(self.proof.iter().copied()),
+        };
+        Ok(serde_json::to_string_pretty(&json)?)
+    }
+
+    pub fn from_json(json: &[u8]) -> Result<Self, EnvelopeError> {
+        let json: EnvelopeJson = serde_json::from_slice(json)?;
+        if json.version != VERSION {
+            return Err(EnvelopeError::Version(json.version));
+        }
+        let mut vk_hash = [0u8; 32];
+        let bytes = from_hex(&json.vk_hash).filter(|bytes| bytes.len() == 32).ok_or_else(|| malformed("bad vk hash"))?;
+        vk_hash.copy_from_slice(&bytes);
+        let transcript = TranscriptKind::from_name(&json.transcript)
+            .ok_or_else(|| EnvelopeError::Malformed(format!("unknown transcript {}", json.transcript)))?;
+        let instances = json
+            .instances
+            .iter()
+            .map(|column| column.iter().map(|value| field_from_hex(value)).collect::<Result<_, _>>())
+            .collect::<Result<_, _>>()?;
+        let proof = from_hex(&json.proof).ok_or_else(|| malformed("bad proof hex"))?;
+        Ok(Self {
+            circuit: json.circuit,
+            k: json.k,
+            vk_hash,
+            transcript,
+            instances,
+            proof,
+        })
+    }
+}
+
+/// The JSON layout of an envelope.
+#[derive(Deserialize, Serialize)]
+struct EnvelopeJson {
+    version: u8,
+    circuit: String,
+    k: u32,
+    vk_hash: String,
+    transcript: String,
+    instances: Vec<Vec<String>>,
+    proof: String,
+}
+
+fn transcript_tag(transcript: TranscriptKind) -> u8 {
+    match transcript {
+        TranscriptKind::Blake2b => 0,
+        TranscriptKind::Keccak256 => 1,
+    }
+}
+
+fn transcript_from_tag(tag: u8) -> Result<TranscriptKind, EnvelopeError> {
+    match tag {
+        0 => Ok(TranscriptKind::Blake2b),
+        1 => Ok(TranscriptKind::Keccak256),
+        tag => Err(EnvelopeError::Malformed(format!("unknown transcript tag {}", tag))),
+    }
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N], EnvelopeError> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads `len` bytes, without allocating them up front.
+fn read_bytes<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>, EnvelopeError> {
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(EnvelopeError::Io(io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(bytes)
+}
+
+fn to_hex(bytes: impl Iterator<Item = u8>) -> String {
+    let digits: String = bytes.map(|byte| format!("{:02x}", byte)).collect();
+    format!("0x{}", digits)
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    let digits = s.strip_prefix("0x")?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// A field element from 64 big-endian hex digits, rejecting values of the
+/// modulus or above.
+fn field_from_hex(s: &str) -> Result<Fr, EnvelopeError> {
+    let bytes = from_hex(s).filter(|bytes| bytes.len() == 32);
+    let bytes = bytes.ok_or_else(|| EnvelopeError::Malformed(format!("{} is not 32 bytes of hex", s)))?;
+    let mut repr = <Fr as PrimeField>::Repr::default();
+    for (byte, be) in repr.as_mut().iter_mut().zip(bytes.iter().rev()) {
+        *byte = *be;
+    }
+    Option::from(Fr::from_repr(repr)).ok_or_else(|| EnvelopeError::Malformed(format!("{} is not a field element", s)))
+}
@@ -1,3 +1,5 @@
+use halo2_tutorial::prover;
+
 
 use std::{marker::PhantomData};
 
@@ -8,12 +10,14 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
+use prover::TranscriptKind;
+
 #[derive(Clone)]
 struct Number<F: FieldExt>(AssignedCell<F, F>);
 
 // Config that contains the columns used in the circuit
 #[derive(Debug, Clone)]
-struct FiboConfig {
+pub(crate) struct FiboConfig {
     a: Column<Advice>,
     b: Column<Advice>,
     c: Column<Advice>,
@@ -68,8 +72,8 @@ impl<F: FieldExt> FiboChip<F> {
     fn load_first_row(
         &self,
         mut layouter: impl Layouter<F>,
-        a: F,
-        b: F,
+        a: Option<F>,
+        b: Option<F>,
     ) -> Result<(Number<F>, Number<F>, Number<F>), Error> {
         // load first row
         layouter.assign_region(
@@ -82,21 +86,21 @@ impl<F: FieldExt> FiboChip<F> {
                     || "a",
                     self.config.a, // column a
                     0, // rotation
-                    || Ok(a),
+                    || a.ok_or(Error::Synthesis),
                 ).map(Number)?;
 
                 let b_num = region.assign_advice(
                     || "b",
                     self.config.b, // column b
                     0, // rotation
-                    || Ok(b),
+                    || b.ok_or(Error::Synthesis),
                 ).map(Number)?;
 
                 let c_num = region.assign_advice(
                     || "c",
                     self.config.c, // column c
                     0, // rotation
-                    || Ok(a + b),
+                    || a.zip(b).map(|(a, b)| a + b).ok_or(Error::Synthesis),
                 ).map(Number)?;
 
                 Ok((a_num, b_num, c_num))
@@ -119,7 +123,7 @@ impl<F: FieldExt> FiboChip<F> {
                 // copy the cell from previous row
                 a.0.copy_advice(|| "a", &mut region, self.config.a, 0)?;
                 b.0.copy_advice(|| "b", &mut region, self.config.b, 0)?;
-                let c = a.0.value().and_then(|a| b.0.value().map(|b| *a + *b));
+                let c = a.0.value().zip(b.0.value()).map(|(a, b)| *a + *b);
 
                 region.assign_advice(
                     || "c",
@@ -142,10 +146,10 @@ impl<F: FieldExt> FiboChip<F> {
 }
 
 #[derive(Default)]
-struct FiboCircuit<F> {
-    a: F,
-    b: F,
-    num: usize,
+pub(crate) struct FiboCircuit<F> {
+    pub(crate) a: Option<F>,
+    pub(crate) b: Option<F>,
+    pub(crate) num: usize,
 }
 
 impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
@@ -153,7 +157,11 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // The sequence length sets the number of rows, so it is kept.
+        Self {
+            num: self.num,
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -185,14 +193,15 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
     }
 }
 
-fn get_fibo_seq(a: u64, b: u64, num: usize) -> Vec<u64> {
+/// The first `num` terms, or `None` if one overflows a `u64`.
+pub(crate) fn get_fibo_seq(a: u64, b: u64, num: usize) -> Option<Vec<u64>> {
     let mut seq = vec![0; num];
     seq[0] = a;
     seq[1] = b;
     for i in 2..num {
-        seq[i] = seq[i - 1] + seq[i - 2];
+        seq[i] = seq[i - 1].checked_add(seq[i - 2])?;
     }
-    seq
+    Some(seq)
 }
 
 fn main() {
@@ -200,14 +209,14 @@ fn main() {
 
     // Prepare the private and public inputs to the circuit!
     let num = 12;
-    let seq = get_fibo_seq(1, 1, num);
+    let seq = get_fibo_seq(1, 1, num).unwrap();
     let res = Fp::from(seq[num - 1]);
     println!("{:?}", seq);
 
     // Instantiate the circuit with the private inputs.
     let circuit = FiboCircuit {
-        a: Fp::from(seq[0]),
-        b: Fp::from(seq[1]),
+        a: Some(Fp::from(seq[0])),
+        b: Some(Fp::from(seq[1])),
         num,
     };
 
@@ -222,8 +231,71 @@ fn main() {
     let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 
+    // Keys generated without the private inputs prove the same statement.
+    println!("{}", prover::round_trip(k, &circuit, &[public_inputs.clone()]).unwrap());
+
+    // Also with the Keccak transcript a Solidity verifier can replay.
+    let instances = [vec![public_inputs.clone()]];
+    let report = prover::round_trip_batch(k, std::slice::from_ref(&circuit), &instances, TranscriptKind::Keccak256);
+    println!("keccak transcript: {}", report.unwrap());
+
     // If we try some other public input, the proof will fail!
     public_inputs[0] += Fp::one();
     let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
     assert!(prover.verify().is_err());
+
+    batch_test(k, num, 8);
+}
+
+/// Proves `n` sequences, each starting from different terms, in one proof
+/// and compares it with proving each sequence on its own.
+fn batch_test(k: u32, num: usize, n: u64) {
+    use halo2_proofs::pairing::bn256::Fr as Fp;
+
+    let (circuits, instances): (Vec<_>, Vec<_>) = (1..=n)
+        .map(|a| {
+            let seq = get_fibo_seq(a, a + 1, num).unwrap();
+            let circuit = FiboCircuit {
+                a: Some(Fp::from(seq[0])),
+                b: Some(Fp::from(seq[1])),
+                num,
+            };
+            (circuit, vec![vec![Fp::from(seq[num - 1])]])
+        })
+        .unzip();
+
+    let batch = prover::round_trip_batch(k, &circuits, &instances, TranscriptKind::Blake2b).unwrap();
+    println!("{} sequences in one proof: {}", n, batch);
+
+    let singles: Vec<_> = circuits
+        .iter()
+        .zip(&instances)
+        .map(|(circuit, instances)| prover::round_trip(k, circuit, instances).unwrap())
+        .collect();
+    println!(
+        "{} sequences in separate proofs: {} bytes in total (prove {:?}, verify {:?})",
+        n,
+        singles.iter().map(|single| single.size()).sum::<usize>(),
+        singles.iter().map(|single| single.prove).sum::<std::time::Duration>(),
+        singles.iter().map(|single| single.verify).sum::<std::time::Duration>(),
+    );
+
+    // A wrong public input for any one sequence fails the whole batch.
+    let (params, params_verifier) = prover::setup(k, 1).unwrap();
+    let pk = prover::keygen(&params, &circuits[0]).unwrap();
+    let vk = pk.get_vk();
+    let transcript = TranscriptKind::Blake2b;
+    let proof = prover::prove_batch(&params, &pk, &circuits, &instances, transcript).unwrap();
+    prover::verify_batch(&params_verifier, vk, &instances, &proof, transcript).unwrap();
+    let mut wrong = instances.clone();
+    wrong[n as usize / 2][0][0] += Fp::one();
+    assert!(prover::verify_batch(&params_verifier, vk, &wrong, &proof, transcript).is_err());
+    // So does verifying against fewer sequences than were proven.
+    assert!(prover::verify_batch(&params_verifier, vk, &instances[1..], &proof, transcript).is_err());
+
+    // Batches with no circuits, or public inputs for a different number of
+    // them, are errors rather than panics.
+    assert!(prover::round_trip_batch::<FiboCircuit<Fp>>(k, &[], &[], transcript).is_err());
+    assert!(prover::round_trip_batch(k, &circuits, &instances[1..], transcript).is_err());
+    assert!(prover::prove_batch(&params, &pk, &circuits[1..], &instances, transcript).is_err());
 }
@@ -1,3 +1,5 @@
+use halo2_tutorial::prover;
+
 use std::{marker::PhantomData};
 
 use halo2_proofs::{
@@ -11,7 +13,7 @@ use halo2_proofs::{
 struct Number<F: FieldExt>(AssignedCell<F, F>);
 
 #[derive(Debug, Clone)]
-struct FiboConfig {
+pub(crate) struct FiboConfig {
     a: Column<Advice>,
     b: Column<Advice>,
     selector: Selector,
@@ -63,8 +65,8 @@ impl<F: FieldExt> FiboChip<F> {
     fn load(
         &self,
         mut layouter: impl Layouter<F>,
-        a: F,
-        b: F,
+        a: Option<F>,
+        b: Option<F>,
         nrows: usize,
     ) -> Result<(Number<F>, Number<F>), Error> {
         layouter.assign_region(
@@ -75,23 +77,20 @@ impl<F: FieldExt> FiboChip<F> {
                     || "a",
                     self.config.a,
                     0,
-                    || Ok(a),
+                    || a.ok_or(Error::Synthesis),
                 ).map(Number)?;
 
                 let mut b = region.assign_advice(
                     || "b",
                     self.config.b,
                     0,
-                    || Ok(b),
+                    || b.ok_or(Error::Synthesis),
                 ).map(Number)?;
-                 println!("[0] a = {:?} b = {:?}", a.0, b.0);
 
                 for idx in 1..nrows {
                     self.config.selector.enable(&mut region, idx)?;
-                    let a2 = a.0.value().and_then(
-                        |a| b.0.value().map(|b| *a + *b),
-                    );
-                    let b2 = a2.and_then(|a| b.0.value().map(|b| a + *b));
+                    let a2 = a.0.value().zip(b.0.value()).map(|(a, b)| *a + *b);
+                    let b2 = a2.zip(b.0.value()).map(|(a, b)| a + *b);
 
                     a = region.assign_advice(
                         || "a",
@@ -106,7 +105,6 @@ impl<F: FieldExt> FiboChip<F> {
                         idx,
                         || b2.ok_or(Error::Synthesis),
                     ).map(Number)?;
-                      println!("[{}] a = {:?} b = {:?}", idx, a.0, b.0);
                 }
 
                 Ok((a, b))
@@ -125,10 +123,10 @@ impl<F: FieldExt> FiboChip<F> {
 }
 
 #[derive(Default)]
-struct FiboCircuit<F> {
-    a: F,
-    b: F,
-    num: usize,
+pub(crate) struct FiboCircuit<F> {
+    pub(crate) a: Option<F>,
+    pub(crate) b: Option<F>,
+    pub(crate) num: usize,
 }
 
 impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
@@ -136,7 +134,11 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // The sequence length sets the number of rows, so it is kept.
+        Self {
+            num: self.num,
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -166,14 +168,15 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
     }
 }
 
-fn get_fibo_seq(a: u64, b: u64, num: usize) -> Vec<u64> {
+/// The first `num` terms, or `None` if one overflows a `u64`.
+pub(crate) fn get_fibo_seq(a: u64, b: u64, num: usize) -> Option<Vec<u64>> {
     let mut seq = vec![0; num];
     seq[0] = a;
     seq[1] = b;
     for i in 2..num {
-        seq[i] = seq[i - 1] + seq[i - 2];
+        seq[i] = seq[i - 1].checked_add(seq[i - 2])?;
     }
-    seq
+    Some(seq)
 }
 
 fn main() {
@@ -181,14 +184,14 @@ fn main() {
 
     // Prepare the private and public inputs to the circuit!
     let num = 16;
-    let seq = get_fibo_seq(1, 1, num);
+    let seq = get_fibo_seq(1, 1, num).unwrap();
     let res = Fp::from(seq[num - 1]);
     println!("{:?}", seq);
 
     // Instantiate the circuit with the private inputs.
     let circuit = FiboCircuit {
-        a: Fp::from(seq[0]),
-        b: Fp::from(seq[1]),
+        a: Some(Fp::from(seq[0])),
+        b: Some(Fp::from(seq[1])),
         num,
     };
 
@@ -203,6 +206,9 @@ fn main() {
     let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 
+    // Keys generated without the private inputs prove the same statement.
+    println!("{}", prover::round_trip(k, &circuit, &[public_inputs.clone()]).unwrap());
+
     // If we try some other public input, the proof will fail!
     public_inputs[0] += Fp::one();
     let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
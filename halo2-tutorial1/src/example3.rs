@@ -1,21 +1,24 @@
+use halo2_tutorial::{convert, prover};
+
 use std::{marker::PhantomData};
 
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner},
+    dev::MockProver,
     plonk::*,
-    poly::{commitment::Params, commitment::ParamsVerifier, Rotation},
-    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+    poly::Rotation,
 };
-use pairing::bn256::{Bn256, Fr as Fp, G1Affine};
-use rand_core::OsRng;
+use pairing::bn256::Fr as Fp;
+
+use convert::{synthesis_error, to_u64};
 
 
 #[derive(Clone, Debug)]
 struct Number<F: FieldExt>(AssignedCell<F, F>);
 
 #[derive(Debug, Clone)]
-struct FiboConfig {
+pub(crate) struct FiboConfig {
     advice: [Column<Advice>; 3],
     s_add: Selector,
     s_xor: Selector,
@@ -97,9 +100,9 @@ impl<F: FieldExt> FiboChip<F> {
     fn load_private(
         &self,
         mut layouter: impl Layouter<F>,
-        a: F,
-        b: F,
-        c: F,
+        a: Option<F>,
+        b: Option<F>,
+        c: Option<F>,
     ) -> Result<(Number<F>, Number<F>, Number<F>), Error> {
         let config = self.config();
 
@@ -110,21 +113,21 @@ impl<F: FieldExt> FiboChip<F> {
                     || "a",
                     config.advice[0],
                     0,
-                    || Ok(a),
+                    || a.ok_or(Error::Synthesis),
                 ).map(Number)?;
 
                 let b_num = region.assign_advice(
                     || "b",
                     config.advice[1],
                     0,
-                    || Ok(b),
+                    || b.ok_or(Error::Synthesis),
                 ).map(Number)?;
 
                 let c_num = region.assign_advice(
                     || "c",
                     config.advice[2],
                     0,
-                    || Ok(c),
+                    || c.ok_or(Error::Synthesis),
                 ).map(Number)?;
 
                 Ok((a_num, b_num, c_num))
@@ -148,7 +151,7 @@ impl<F: FieldExt> FiboChip<F> {
                 a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
                 b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
 
-                let value = a.0.value().and_then(|a| b.0.value().map(|b| *a + *b));
+                let value = a.0.value().zip(b.0.value()).map(|(a, b)| *a + *b);
                 // println!("add row: {:?}, {:?}, {:?}", a.0.value(), b.0.value(), value);
 
                 region.assign_advice(
@@ -176,11 +179,11 @@ impl<F: FieldExt> FiboChip<F> {
                 a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
                 b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
 
-                let value = a.0.value().and_then(|a| b.0.value().map(|b| {
-                    let a_val = a.get_lower_128() as u64;
-                    let b_val = b.get_lower_128() as u64;
-                    F::from(a_val ^ b_val)
-                }));
+                let value = a.0.value().zip(b.0.value()).map(|(a, b)| {
+                    let a_val = to_u64(a, "xor lhs").map_err(synthesis_error)?;
+                    let b_val = to_u64(b, "xor rhs").map_err(synthesis_error)?;
+                    Ok::<_, Error>(F::from(a_val ^ b_val))
+                }).transpose()?;
               //  println!("xor row: {:?}, {:?}, {:?}", a.0.value(), b.0.value(), value);
 
                 region.assign_advice(
@@ -230,12 +233,12 @@ impl<F: FieldExt> FiboChip<F> {
     }
 }
 
-#[derive(Default)]
-struct FiboCircuit<F> {
-    a: F,
-    b: F,
-    c: F,
-    num: usize,
+#[derive(Clone, Default)]
+pub(crate) struct FiboCircuit<F> {
+    pub(crate) a: Option<F>,
+    pub(crate) b: Option<F>,
+    pub(crate) c: Option<F>,
+    pub(crate) num: usize,
 }
 
 impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
@@ -243,7 +246,11 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // The sequence length sets the number of rows, so it is kept.
+        Self {
+            num: self.num,
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -290,61 +297,44 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
     }
 }
 
-fn get_sequence(a: u64, b: u64, c: u64, num: usize) -> Vec<u64> {
+/// The first `num` terms, or `None` if one overflows a `u64`.
+pub(crate) fn get_sequence(a: u64, b: u64, c: u64, num: usize) -> Option<Vec<u64>> {
     let mut seq = vec![0; num];
     seq[0] = a;
     seq[1] = b;
     seq[2] = c;
     for i in 3..num {
-        seq[i] = seq[i - 3] + (seq[i - 2] ^ seq[i - 1]);
+        seq[i] = seq[i - 3].checked_add(seq[i - 2] ^ seq[i - 1])?;
     }
-    seq
+    Some(seq)
 }
 
 fn main() {
     // Prepare the private and public inputs to the circuit!
     let num = 14;
-    let seq = get_sequence(1, 3, 2, num);
+    let seq = get_sequence(1, 3, 2, num).unwrap();
     println!("{:?}", seq);
 
     // Instantiate the circuit with the private inputs.
     let circuit = FiboCircuit {
-        a: Fp::from(seq[0]),
-        b: Fp::from(seq[1]),
-        c: Fp::from(seq[2]),
+        a: Some(Fp::from(seq[0])),
+        b: Some(Fp::from(seq[1])),
+        c: Some(Fp::from(seq[2])),
         num,
     };
 
     // Set circuit size
     let k = 11;
 
-    // Initialize the polynomial commitment parameters
-    let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(k);
-    let params_verifier: ParamsVerifier<Bn256> = params.verifier(0).unwrap();
-
-    // Initialize the proving key and verification key
-    let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
-    let pk = keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail");
-
-    // Create a proof
-    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-
-    create_proof(&params, &pk, &[circuit], &[&[]], OsRng, &mut transcript)
-        .expect("proof generation should not fail");
-
-    let proof = transcript.finalize();
-    println!("proof size is {}", proof.len());
-
-    // Verify the proof
-    let strategy = SingleVerifier::new(&params_verifier);
-    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
-
-    verify_proof(
-        &params_verifier,
-        pk.get_vk(),
-        strategy,
-        &[&[]],
-        &mut transcript,
-    )
-    .unwrap();
+    // Generate the keys without the witness, since they only depend on the
+    // circuit's shape, then prove and verify.
+    let report = prover::round_trip(k, &circuit, &[]).expect("proof should verify");
+    println!("{}", report);
+
+    // A term that does not fit a u64 has no xor, and the failure names the cell.
+    let circuit = FiboCircuit { b: Some(-Fp::one()), ..circuit };
+    assert!(matches!(MockProver::run(k, &circuit, vec![]), Err(Error::Synthesis)));
+    let err = convert::take_last().unwrap();
+    assert_eq!(err.name, "xor lhs");
+    println!("{}", err);
 }
@@ -1,3 +1,5 @@
+use halo2_tutorial::prover;
+
 
 use std::{marker::PhantomData};
 
@@ -137,7 +139,7 @@ impl<F: FieldExt> FabonacciInstruction<F> for FabonacciChip<F> {
                     || "a + b",
                     config.c,
                     0,
-                    || Ok(a_cell.value().copied().unwrap() + b_cell.value().copied().unwrap()),
+                    || a_cell.value().zip(b_cell.value()).map(|(a, b)| *a + *b).ok_or(Error::Synthesis),
                 )?;
 
                 Ok((a_cell, b_cell, c_cell))
@@ -159,13 +161,13 @@ impl<F: FieldExt> FabonacciInstruction<F> for FabonacciChip<F> {
 
                 prev_c.copy_advice(|| "cp to b", &mut region, config.b, 0)?;
 
-                let res =  prev_b.value().and_then(|a| prev_c.value().map(|b| *a + *b)).unwrap();
+                let res = prev_b.value().zip(prev_c.value()).map(|(a, b)| *a + *b);
 
                 let c_cell = region.assign_advice(
                     || "c",
                     config.c,
                     0,
-                    || Ok(res),
+                    || res.ok_or(Error::Synthesis),
                 )?;
 
                 Ok(c_cell)
@@ -193,7 +195,9 @@ impl<F: FieldExt> Circuit<F> for MyCircuit {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // Every witness comes from the instance column; only the length is
+        // part of the circuit.
+        Self { k: self.k }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -242,6 +246,7 @@ fn main() {
     let prover = MockProver::run(4, &my_circuit, vec![public_input.clone()]).unwrap();
 
     assert_eq!(prover.verify(), Ok(()));
+    println!("{}", prover::round_trip(4, &my_circuit, &[public_input.clone()]).unwrap());
 
     public_input[2] += Fp::one();
 
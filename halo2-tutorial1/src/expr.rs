@@ -0,0 +1,182 @@
+#![allow(dead_code)]
+
+use halo2_proofs::{arithmetic::FieldExt, pairing::bn256::Fr, plonk::Expression};
+
+pub trait Expr<F: FieldExt> {
+    fn expr(&self) -> Expression<F>;
+
+    /// `a AND b`, for boolean `a` and `b`.
+    fn and<E: Expr<F>>(&self, other: E) -> Expression<F> {
+        self.expr() * other.expr()
+    }
+
+    /// `a OR b`, for boolean `a` and `b`.
+    fn or<E: Expr<F>>(&self, other: E) -> Expression<F> {
+        let (a, b) = (self.expr(), other.expr());
+        a.clone() + b.clone() - a * b
+    }
+
+    /// `NOT a`, for boolean `a`.
+    fn not(&self) -> Expression<F> {
+        Expression::Constant(F::one()) - self.expr()
+    }
+
+    /// `a XOR b`, for boolean `a` and `b`.
+    fn xor<E: Expr<F>>(&self, other: E) -> Expression<F> {
+        let (a, b) = (self.expr(), other.expr());
+        a.clone() + b.clone() - Expression::Constant(F::from(2)) * a * b
+    }
+
+    /// `NOT (a AND b)`, for boolean `a` and `b`.
+    fn nand<E: Expr<F>>(&self, other: E) -> Expression<F> {
+        self.and(other).not()
+    }
+}
+
+macro_rules! impl_expr {
+    ($type:ty) => {
+        impl<F: halo2_proofs::arithmetic::FieldExt> Expr<F> for $type {
+            #[inline]
+            fn expr(&self) -> Expression<F> {
+                Expression::Constant(F::from(*self as u64))
+            }
+        }
+    };
+}
+impl_expr!(bool);
+impl_expr!(u8);
+impl_expr!(u16);
+impl_expr!(u32);
+impl_expr!(u64);
+impl_expr!(usize);
+impl<F: FieldExt> Expr<F> for Expression<F> {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        self.clone()
+    }
+}
+
+impl<F: FieldExt> Expr<F> for &Expression<F> {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        (*self).clone()
+    }
+}
+impl<F: FieldExt> Expr<F> for i32 {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        Expression::Constant(
+            F::from(self.unsigned_abs() as u64)
+                * if self.is_negative() {
+                -F::one()
+            } else {
+                F::one()
+            },
+        )
+    }
+}
+// A blanket `impl<F: FieldExt> Expr<F> for F` would conflict with the integer
+// impls above, so field elements get the circuit field only. Generic chips use
+// `constant` instead.
+impl Expr<Fr> for Fr {
+    #[inline]
+    fn expr(&self) -> Expression<Fr> {
+        Expression::Constant(*self)
+    }
+}
+
+/// Returns a constant expression for a field element.
+pub fn constant<F: FieldExt>(value: F) -> Expression<F> {
+    Expression::Constant(value)
+}
+
+/// Given a bytes-representation of an expression, it computes and returns the
+/// single expression.
+pub fn expr_from_bytes<F: FieldExt, E: Expr<F>>(bytes: &[E]) -> Expression<F> {
+    let mut value = Expression::Constant(F::from(0));
+    let mut multiplier = F::one();
+    for byte in bytes.iter() {
+        value = value + byte.expr() * multiplier;
+        multiplier *= F::from(256);
+    }
+    value
+}
+/// Given a bits-representation of an expression, least significant bit first,
+/// it computes and returns the single expression.
+pub fn expr_from_bits<F: FieldExt, E: Expr<F>>(bits: &[E]) -> Expression<F> {
+    let mut value = Expression::Constant(F::from(0));
+    let mut multiplier = F::one();
+    for bit in bits.iter() {
+        value = value + bit.expr() * multiplier;
+        multiplier = multiplier.double();
+    }
+    value
+}
+
+/// Returns the sum of `items`, or 0 if there are none.
+pub fn sum<F: FieldExt, E: Expr<F>>(items: impl IntoIterator<Item = E>) -> Expression<F> {
+    items
+        .into_iter()
+        .fold(Expression::Constant(F::zero()), |acc, item| acc + item.expr())
+}
+
+/// Returns the product of `items`, or 1 if there are none.
+pub fn product<F: FieldExt, E: Expr<F>>(items: impl IntoIterator<Item = E>) -> Expression<F> {
+    items
+        .into_iter()
+        .fold(Expression::Constant(F::one()), |acc, item| acc * item.expr())
+}
+
+/// Returns `when_true` if `cond` is 1 and `when_false` if it is 0. `cond` must
+/// be constrained to a boolean separately.
+pub fn select<F: FieldExt>(
+    cond: impl Expr<F>,
+    when_true: impl Expr<F>,
+    when_false: impl Expr<F>,
+) -> Expression<F> {
+    let cond = cond.expr();
+    cond.clone() * when_true.expr() + cond.not() * when_false.expr()
+}
+
+/// Returns `1 - value`, for a boolean `value`.
+pub fn not<F: FieldExt>(value: impl Expr<F>) -> Expression<F> {
+    value.not()
+}
+
+/// Returns 1 if all `items` are 1, for boolean `items`.
+pub fn and<F: FieldExt, E: Expr<F>>(items: impl IntoIterator<Item = E>) -> Expression<F> {
+    product(items)
+}
+
+/// Returns 1 if any of `items` is 1, for boolean `items`.
+pub fn or<F: FieldExt, E: Expr<F>>(items: impl IntoIterator<Item = E>) -> Expression<F> {
+    not(product(items.into_iter().map(|item| item.not())))
+}
+
+/// Returns 1 if `value` is 0 and 0 otherwise, given a witness `value_inv` for
+/// the inverse of `value`. Only sound together with `is_zero_check`.
+pub fn is_zero<F: FieldExt>(value: impl Expr<F>, value_inv: impl Expr<F>) -> Expression<F> {
+    Expression::Constant(F::one()) - value.expr() * value_inv.expr()
+}
+
+/// The constraint `value * is_zero(value, value_inv) == 0`. It forces
+/// `value_inv` to be the inverse of a non-zero `value`, so `is_zero` cannot be
+/// 1 for a non-zero `value`.
+pub fn is_zero_check<F: FieldExt>(value: impl Expr<F>, value_inv: impl Expr<F>) -> Expression<F> {
+    let value = value.expr();
+    value.clone() * is_zero(value, value_inv)
+}
+
+/// Restrict an expression to be a boolean.
+pub fn bool_check<F: FieldExt>(value: Expression<F>) -> Expression<F> {
+    range_check(value, 2)
+}
+
+/// Restrict an expression such that 0 <= word < range.
+/// The result has degree `range`; `range::RangeCheckChip` switches to a lookup
+/// for ranges that do not fit a degree budget.
+pub fn range_check<F: FieldExt>(word: Expression<F>, range: usize) -> Expression<F> {
+    (1..range).fold(word.clone(), |acc, i| {
+        acc * (Expression::Constant(F::from(i as u64)) - word.clone())
+    })
+}
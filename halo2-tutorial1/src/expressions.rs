@@ -0,0 +1,111 @@
+use halo2_tutorial::expr;
+
+use halo2_proofs::{arithmetic::FieldExt, pairing::bn256::Fr as Fp, plonk::Expression};
+
+use expr::{
+    and, bool_check, constant, expr_from_bits, expr_from_bytes, is_zero, is_zero_check, not, or, product,
+    range_check, select, sum, Expr,
+};
+
+/// Evaluates an expression built from constants only.
+fn eval(e: &Expression<Fp>) -> Fp {
+    e.evaluate(
+        &|c| c,
+        &|_| unreachable!("selector"),
+        &|_, _, _| unreachable!("fixed column"),
+        &|_, _, _| unreachable!("advice column"),
+        &|_, _, _| unreachable!("instance column"),
+        &|a| -a,
+        &|a, b| a + b,
+        &|a, b| a * b,
+        &|a, scalar| a * scalar,
+    )
+}
+
+fn e<T: Expr<Fp>>(value: T) -> Fp {
+    eval(&value.expr())
+}
+
+fn constants_test() {
+    assert_eq!(e(true), Fp::one());
+    assert_eq!(e(false), Fp::zero());
+    assert_eq!(e(u8::MAX), Fp::from(255));
+    assert_eq!(e(u16::MAX), Fp::from(65535));
+    assert_eq!(e(u32::MAX), Fp::from(u32::MAX as u64));
+    assert_eq!(e(u64::MAX), Fp::from(u64::MAX));
+    assert_eq!(e(42usize), Fp::from(42));
+    assert_eq!(e(-7i32), -Fp::from(7));
+    assert_eq!(e(Fp::from(9)), Fp::from(9));
+    assert_eq!(eval(&constant(-Fp::one())), -Fp::one());
+}
+
+fn composition_test() {
+    // 13 = 0b1101, least significant bit first.
+    assert_eq!(eval(&expr_from_bits(&[1u64, 0, 1, 1])), Fp::from(13));
+    assert_eq!(eval(&expr_from_bits::<Fp, u64>(&[])), Fp::zero());
+    assert_eq!(eval(&expr_from_bytes(&[0x34u8, 0x12])), Fp::from(0x1234));
+
+    let items = [3u64, 5, 7];
+    assert_eq!(eval(&sum(items)), Fp::from(15));
+    assert_eq!(eval(&product(items)), Fp::from(105));
+    assert_eq!(eval(&sum::<Fp, u64>([])), Fp::zero());
+    assert_eq!(eval(&product::<Fp, u64>([])), Fp::one());
+
+    assert_eq!(eval(&select(1u64, 10u64, 20u64)), Fp::from(10));
+    assert_eq!(eval(&select(0u64, 10u64, 20u64)), Fp::from(20));
+}
+
+fn boolean_test() {
+    for a in [false, true] {
+        assert_eq!(eval(&not(a)), Fp::from(!a as u64));
+
+        for b in [false, true] {
+            assert_eq!(eval(&a.and(b)), Fp::from((a & b) as u64));
+            assert_eq!(eval(&a.or(b)), Fp::from((a | b) as u64));
+            assert_eq!(eval(&a.xor(b)), Fp::from((a ^ b) as u64));
+            assert_eq!(eval(&a.nand(b)), Fp::from(!(a & b) as u64));
+
+            for c in [false, true] {
+                assert_eq!(eval(&and([a, b, c])), Fp::from((a & b & c) as u64));
+                assert_eq!(eval(&or([a, b, c])), Fp::from((a | b | c) as u64));
+            }
+        }
+    }
+}
+
+fn inverse<F: FieldExt>(value: F) -> F {
+    value.invert().unwrap()
+}
+
+fn is_zero_test() {
+    let five = Fp::from(5);
+    let five_inv = inverse(five);
+
+    // Zero is zero whatever the inverse witness is.
+    for inv in [Fp::zero(), five] {
+        assert_eq!(eval(&is_zero(Fp::zero(), inv)), Fp::one());
+        assert_eq!(eval(&is_zero_check(Fp::zero(), inv)), Fp::zero());
+    }
+
+    assert_eq!(eval(&is_zero(five, five_inv)), Fp::zero());
+    assert_eq!(eval(&is_zero_check(five, five_inv)), Fp::zero());
+
+    // A wrong inverse claims 5 is zero, and fails the check.
+    assert_eq!(eval(&is_zero(five, Fp::zero())), Fp::one());
+    assert_ne!(eval(&is_zero_check(five, Fp::zero())), Fp::zero());
+}
+
+fn range_test() {
+    for v in 0..10u64 {
+        assert_eq!(eval(&bool_check(v.expr())) == Fp::zero(), v < 2, "{}", v);
+        assert_eq!(eval(&range_check(v.expr(), 5)) == Fp::zero(), v < 5, "{}", v);
+    }
+}
+
+fn main() {
+    constants_test();
+    composition_test();
+    boolean_test();
+    is_zero_test();
+    range_test();
+}
@@ -1,3 +1,4 @@
+use halo2_tutorial::{convert, prover};
 
 
 use std::{marker::PhantomData};
@@ -14,6 +15,8 @@ struct Number<F: FieldExt>(AssignedCell<F, F>);
 
 use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
 
+use convert::{synthesis_error, to_u64};
+
 #[derive(Debug, Clone)]
 struct FibonacciConfig {
     pub advice: [Column<Advice>; 3],
@@ -185,11 +188,11 @@ impl<F: FieldExt> FibonacciChip<F> {
                     } else {
                         self.config.s_xor.enable(&mut region, row)?;
 
-                        let c = b_cell.value().and_then(|a| c_cell.value().map(|b| {
-                            let a_val = a.get_lower_128() as u64 ;
-                            let b_val = b.get_lower_128() as u64 ;
-                            F::from( a_val ^ b_val)
-                        }));
+                        let c = b_cell.value().zip(c_cell.value()).map(|(a, b)| {
+                            let a_val = to_u64(a, &format!("xor lhs at row {}", row)).map_err(synthesis_error)?;
+                            let b_val = to_u64(b, &format!("xor rhs at row {}", row)).map_err(synthesis_error)?;
+                            Ok::<_, Error>(F::from(a_val ^ b_val))
+                        }).transpose()?;
                         region.assign_advice(
                             || "advice",
                             self.config.advice[2],
@@ -258,7 +261,10 @@ fn main() {
 
     let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
+    println!("{}", prover::round_trip(k, &circuit, &[public_input]).unwrap());
 
-
-
+    // The xor rows need the previous values as integers; a field element
+    // wider than 64 bits stops synthesis instead of being truncated.
+    let public_input = vec![a, -Fp::one(), out];
+    assert!(MockProver::run(k, &circuit, vec![public_input]).is_err());
 }
\ No newline at end of file
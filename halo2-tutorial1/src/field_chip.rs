@@ -0,0 +1,297 @@
+#![allow(dead_code)]
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+/// A variable representing a number.
+#[derive(Clone, Debug)]
+pub struct Number<F: FieldExt>(pub AssignedCell<F, F>);
+
+/// The operations implemented by `FieldChip`. Every operation owns its own
+/// selector, so enabling one gate never constrains a region with another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Square,
+    Neg,
+    Inv,
+}
+
+impl Op {
+    /// Returns true if the operation only reads `lhs`.
+    pub fn is_unary(&self) -> bool {
+        matches!(self, Op::Square | Op::Neg | Op::Inv)
+    }
+
+    /// Evaluates the operation natively. The inverse of zero is zero, so a
+    /// zero divisor produces a witness that the `inv` gate rejects.
+    pub fn eval<F: FieldExt>(&self, lhs: F, rhs: F) -> F {
+        match self {
+            Op::Add => lhs + rhs,
+            Op::Sub => lhs - rhs,
+            Op::Mul => lhs * rhs,
+            Op::Square => lhs.square(),
+            Op::Neg => -lhs,
+            Op::Inv => lhs.invert().unwrap_or(F::zero()),
+        }
+    }
+}
+
+pub trait NumericInstruction<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Loads a number into the circuit as a private input.
+    fn load_private(&self, layouter: impl Layouter<F>, a: Option<F>) -> Result<Self::Num, Error>;
+
+    /// Loads a number into the circuit as a fixed constant.
+    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a + b`.
+    fn add(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a - b`.
+    fn sub(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a * b`.
+    fn mul(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a * a`.
+    fn square(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = -a`.
+    fn neg(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = 1 / a`. The circuit is unsatisfiable if `a` is zero.
+    fn inv(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a / b`. The circuit is unsatisfiable if `b` is zero.
+    fn div(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldConfig {
+    pub advice: [Column<Advice>; 2],
+    pub instance: Column<Instance>,
+    s_add: Selector,
+    s_sub: Selector,
+    s_mul: Selector,
+    s_square: Selector,
+    s_neg: Selector,
+    s_inv: Selector,
+}
+
+impl FieldConfig {
+    fn selector(&self, op: Op) -> Selector {
+        match op {
+            Op::Add => self.s_add,
+            Op::Sub => self.s_sub,
+            Op::Mul => self.s_mul,
+            Op::Square => self.s_square,
+            Op::Neg => self.s_neg,
+            Op::Inv => self.s_inv,
+        }
+    }
+}
+
+pub struct FieldChip<F: FieldExt> {
+    config: FieldConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for FieldChip<F> {
+    type Config = FieldConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> FieldChip<F> {
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> <Self as Chip<F>>::Config {
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for col in &advice {
+            meta.enable_equality(*col);
+        }
+
+        let config = FieldConfig {
+            advice,
+            instance,
+            s_add: meta.selector(),
+            s_sub: meta.selector(),
+            s_mul: meta.selector(),
+            s_square: meta.selector(),
+            s_neg: meta.selector(),
+            s_inv: meta.selector(),
+        };
+
+        // All gates share one layout:
+        //
+        // | a0  | a1  | selector |
+        // |-----|-----|----------|
+        // | lhs | rhs |    s     |
+        // | out |     |          |
+        //
+        // Unary gates leave `rhs` unassigned and never query it.
+        let ops: [(&'static str, Op); 6] = [
+            ("add", Op::Add),
+            ("sub", Op::Sub),
+            ("mul", Op::Mul),
+            ("square", Op::Square),
+            ("neg", Op::Neg),
+            ("inv", Op::Inv),
+        ];
+        for (name, op) in ops {
+            let selector = config.selector(op);
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let lhs = meta.query_advice(advice[0], Rotation::cur());
+                let out = meta.query_advice(advice[0], Rotation::next());
+                let poly = match op {
+                    Op::Add => lhs + meta.query_advice(advice[1], Rotation::cur()) - out,
+                    Op::Sub => lhs - meta.query_advice(advice[1], Rotation::cur()) - out,
+                    Op::Mul => lhs * meta.query_advice(advice[1], Rotation::cur()) - out,
+                    Op::Square => lhs.clone() * lhs - out,
+                    Op::Neg => lhs + out,
+                    // There is no `out` with `0 * out == 1`, which is what
+                    // rejects a zero divisor in `div`.
+                    Op::Inv => lhs * out - Expression::Constant(F::one()),
+                };
+                vec![s * poly]
+            });
+        }
+
+        config
+    }
+
+    /// Assigns a single `op` region with `out` as the claimed result. The
+    /// instructions compute `out` honestly; tests use this directly to check
+    /// that a tampered result is rejected.
+    pub fn assign_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: Op,
+        a: &Number<F>,
+        b: Option<&Number<F>>,
+        out: Option<F>,
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || format!("{:?}", op),
+            |mut region: Region<'_, F>| {
+                config.selector(op).enable(&mut region, 0)?;
+
+                a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                if let Some(b) = b {
+                    b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                }
+
+                region
+                    .assign_advice(|| "out", config.advice[0], 1, || out.ok_or(Error::Synthesis))
+                    .map(Number)
+            },
+        )
+    }
+
+    fn binary_op(
+        &self,
+        layouter: impl Layouter<F>,
+        op: Op,
+        a: Number<F>,
+        b: Number<F>,
+    ) -> Result<Number<F>, Error> {
+        let out = a.0.value().zip(b.0.value()).map(|(a, b)| op.eval(*a, *b));
+        self.assign_op(layouter, op, &a, Some(&b), out)
+    }
+
+    fn unary_op(&self, layouter: impl Layouter<F>, op: Op, a: Number<F>) -> Result<Number<F>, Error> {
+        let out = a.0.value().map(|a| op.eval(*a, F::zero()));
+        self.assign_op(layouter, op, &a, None, out)
+    }
+}
+
+impl<F: FieldExt> NumericInstruction<F> for FieldChip<F> {
+    type Num = Number<F>;
+
+    fn load_private(&self, mut layouter: impl Layouter<F>, a: Option<F>) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(|| "load private", |mut region| {
+            region.assign_advice(|| "private input", config.advice[0], 0, || a.ok_or(Error::Synthesis)).map(Number)
+        })
+    }
+
+    fn load_constant(&self, mut layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(|| "load constant", |mut region| {
+            region.assign_advice_from_constant(|| "constant input", config.advice[0], 0, constant).map(Number)
+        })
+    }
+
+    fn add(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error> {
+        self.binary_op(layouter, Op::Add, a, b)
+    }
+
+    fn sub(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error> {
+        self.binary_op(layouter, Op::Sub, a, b)
+    }
+
+    fn mul(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error> {
+        self.binary_op(layouter, Op::Mul, a, b)
+    }
+
+    fn square(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        self.unary_op(layouter, Op::Square, a)
+    }
+
+    fn neg(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        self.unary_op(layouter, Op::Neg, a)
+    }
+
+    fn inv(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        self.unary_op(layouter, Op::Inv, a)
+    }
+
+    fn div(&self, mut layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error> {
+        let b_inv = self.inv(layouter.namespace(|| "1 / b"), b)?;
+        self.mul(layouter.namespace(|| "a * (1 / b)"), a, b_inv)
+    }
+
+    fn expose_public(&self, mut layouter: impl Layouter<F>, num: Self::Num, row: usize) -> Result<(), Error> {
+        let config = self.config();
+        layouter.constrain_instance(num.0.cell(), config.instance, row)
+    }
+}
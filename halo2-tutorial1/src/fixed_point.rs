@@ -0,0 +1,554 @@
+use halo2_tutorial::{convert, prover, table};
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+
+use convert::{synthesis_error, to_u64};
+use table::RangTableConfig;
+
+/// A non-negative fixed-point number `v`, stored as the integer `v * scale`.
+#[derive(Clone, Debug)]
+struct FixedPoint<F: FieldExt>(AssignedCell<F, F>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+trait FixedPointInstructions<F: FieldExt>: Chip<F> {
+    /// Loads a scaled integer into the circuit as a private input.
+    fn load_private(&self, layouter: impl Layouter<F>, value: Option<u64>) -> Result<FixedPoint<F>, Error>;
+
+    /// Returns `a + b`.
+    fn add(&self, layouter: impl Layouter<F>, a: &FixedPoint<F>, b: &FixedPoint<F>) -> Result<FixedPoint<F>, Error>;
+
+    /// Returns `a - b`. The circuit is unsatisfiable if `a < b`.
+    fn sub(&self, layouter: impl Layouter<F>, a: &FixedPoint<F>, b: &FixedPoint<F>) -> Result<FixedPoint<F>, Error>;
+
+    /// Returns `a * b`, rounded down to the scale.
+    fn mul(&self, layouter: impl Layouter<F>, a: &FixedPoint<F>, b: &FixedPoint<F>) -> Result<FixedPoint<F>, Error>;
+
+    /// Returns `a / b`, rounded down to the scale. The circuit is unsatisfiable
+    /// if `b` is zero.
+    fn div(&self, layouter: impl Layouter<F>, a: &FixedPoint<F>, b: &FixedPoint<F>) -> Result<FixedPoint<F>, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(&self, layouter: impl Layouter<F>, num: &FixedPoint<F>, row: usize) -> Result<(), Error>;
+}
+
+/// Every number handled by the chip is range checked to `N_BYTES` bytes, so
+/// products of two numbers never wrap around the field.
+#[derive(Clone, Debug)]
+struct FixedPointConfig<F: FieldExt, const N_BYTES: usize> {
+    /// `x`, `y` and `z` in the layouts below.
+    advice: [Column<Advice>; 3],
+    /// Little-endian bytes of `x` on rows where `s_range` is enabled.
+    bytes: [Column<Advice>; N_BYTES],
+    instance: Column<Instance>,
+    s_add: Selector,
+    s_sub: Selector,
+    s_mul: Selector,
+    s_div: Selector,
+    s_range: Selector,
+    table: RangTableConfig<F>,
+    /// The number of units in `1.0`.
+    scale: u64,
+}
+
+struct FixedPointChip<F: FieldExt, const N_BYTES: usize> {
+    config: FixedPointConfig<F, N_BYTES>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> Chip<F> for FixedPointChip<F, N_BYTES> {
+    type Config = FixedPointConfig<F, N_BYTES>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const N_BYTES: usize> FixedPointChip<F, N_BYTES> {
+    fn construct(config: FixedPointConfig<F, N_BYTES>) -> Self {
+        Self { config }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, scale: u64) -> FixedPointConfig<F, N_BYTES> {
+        // Two numbers of 15 bytes multiply to 240 bits, below the modulus,
+        // and the shift below needs fewer than 16 bytes too.
+        assert!(N_BYTES < 16, "numbers must fit in fewer than 16 bytes, not {}", N_BYTES);
+        assert!(
+            scale > 0 && (scale as u128) <= 1u128 << (8 * N_BYTES),
+            "scale must fit in {} bytes",
+            N_BYTES
+        );
+
+        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let bytes = [(); N_BYTES].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let s_add = meta.selector();
+        let s_sub = meta.selector();
+        let s_mul = meta.selector();
+        let s_div = meta.selector();
+        let s_range = meta.complex_selector();
+        let table = RangTableConfig::configure(meta);
+
+        meta.enable_equality(instance);
+        for col in &advice {
+            meta.enable_equality(*col);
+        }
+
+        let [x, y, z] = advice;
+        let one = Expression::Constant(F::one());
+        let scale_expr = Expression::Constant(F::from(scale));
+
+        // |  x  | bytes[0..N] | s_range |
+        // |-----|-------------|---------|
+        // |  v  |   b0 .. bN  |    1    |
+        //
+        // v = b0 + b1 * 256 + ... + bN * 256^N, with every byte in the table.
+        meta.create_gate("range", |meta| {
+            let s = meta.query_selector(s_range);
+            let v = meta.query_advice(x, Rotation::cur());
+            let recomposed = bytes.iter().rev().fold(Expression::Constant(F::zero()), |acc, byte| {
+                acc * Expression::Constant(F::from(256)) + meta.query_advice(*byte, Rotation::cur())
+            });
+            vec![s * (v - recomposed)]
+        });
+        for byte in bytes {
+            meta.lookup("byte", |meta| {
+                let s = meta.query_selector(s_range);
+                let byte = meta.query_advice(byte, Rotation::cur());
+                vec![(s * byte, table.col_value)]
+            });
+        }
+
+        // |  x  |  y  |  z  | s_add / s_sub |
+        // |-----|-----|-----|---------------|
+        // |  a  |  b  |  c  |       1       |
+        // |  c  |     |     |               |  <- s_range
+        for (name, selector, op) in [("add", s_add, Op::Add), ("sub", s_sub, Op::Sub)] {
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let a = meta.query_advice(x, Rotation::cur());
+                let b = meta.query_advice(y, Rotation::cur());
+                let c = meta.query_advice(z, Rotation::cur());
+                let c_range = meta.query_advice(x, Rotation::next());
+                let poly = if op == Op::Add { a + b - c.clone() } else { a - b - c.clone() };
+                vec![s.clone() * poly, s * (c_range - c)]
+            });
+        }
+
+        // Rescaling witnesses a quotient `q` and remainder `r`, and bounds `r`
+        // by checking that both `r` and `bound - 1 - r` fit in N_BYTES:
+        //
+        // |        x        |  y  |  z  | s_mul / s_div |
+        // |-----------------|-----|-----|---------------|
+        // |        a        |  b  |  q  |       1       |
+        // |        r        |     |     |               |  <- s_range
+        // | bound - 1 - r   |     |     |               |  <- s_range
+        // |        q        |     |     |               |  <- s_range
+        //
+        // mul: a * b = q * scale + r, bound = scale
+        // div: a * scale = q * b + r, bound = b
+        for (name, selector, op) in [("mul", s_mul, Op::Mul), ("div", s_div, Op::Div)] {
+            let one = one.clone();
+            let scale_expr = scale_expr.clone();
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let a = meta.query_advice(x, Rotation::cur());
+                let b = meta.query_advice(y, Rotation::cur());
+                let q = meta.query_advice(z, Rotation::cur());
+                let r = meta.query_advice(x, Rotation::next());
+                let slack = meta.query_advice(x, Rotation(2));
+                let q_range = meta.query_advice(x, Rotation(3));
+
+                let (identity, bound) = if op == Op::Mul {
+                    (a * b - q.clone() * scale_expr.clone() - r.clone(), scale_expr)
+                } else {
+                    (a * scale_expr - q.clone() * b.clone() - r.clone(), b)
+                };
+                vec![
+                    s.clone() * identity,
+                    s.clone() * (bound - one - r - slack),
+                    s * (q_range - q),
+                ]
+            });
+        }
+
+        FixedPointConfig {
+            advice,
+            bytes,
+            instance,
+            s_add,
+            s_sub,
+            s_mul,
+            s_div,
+            s_range,
+            table,
+            scale,
+        }
+    }
+
+    fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.config.table.load(&mut layouter, (0..256).collect())
+    }
+
+    /// Assigns `value` to `x` at `offset` together with its bytes.
+    fn assign_range(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Option<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        config.s_range.enable(region, offset)?;
+
+        let repr = value.map(|v| v.to_repr());
+        for (idx, column) in config.bytes.iter().enumerate() {
+            let byte = repr.as_ref().map(|repr| F::from(repr.as_ref()[idx] as u64));
+            region.assign_advice(
+                || format!("byte {}", idx),
+                *column,
+                offset,
+                || byte.ok_or(Error::Synthesis),
+            )?;
+        }
+        region.assign_advice(|| "x", config.advice[0], offset, || value.ok_or(Error::Synthesis))
+    }
+
+    /// Computes the honest `(result, remainder)` witness for `op`.
+    fn witness(&self, op: Op, a: F, b: F) -> Result<(F, F), Error> {
+        let scale = self.config.scale as u128;
+        // Both operands are at most 8 bytes, so their product fits in a u128.
+        let int = |value: &F, name| to_u64(value, name).map(u128::from).map_err(synthesis_error);
+        let ints = || Ok::<_, Error>((int(&a, "a")?, int(&b, "b")?));
+        Ok(match op {
+            Op::Add => (a + b, F::zero()),
+            Op::Sub => (a - b, F::zero()),
+            Op::Mul => {
+                let (a_int, b_int) = ints()?;
+                let prod = a_int * b_int;
+                (F::from_u128(prod / scale), F::from_u128(prod % scale))
+            }
+            Op::Div => {
+                let (a_int, b_int) = ints()?;
+                let num = a_int * scale;
+                match (num.checked_div(b_int), num.checked_rem(b_int)) {
+                    (Some(q), Some(r)) => (F::from_u128(q), F::from_u128(r)),
+                    // There is no valid witness for a zero divisor; this one
+                    // fails the `bound - 1 - r` range check.
+                    _ => (F::zero(), F::from_u128(num)),
+                }
+            }
+        })
+    }
+
+    /// Assigns one `op` region with the given `(result, remainder)` witness.
+    fn assign_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: Op,
+        a: &FixedPoint<F>,
+        b: &FixedPoint<F>,
+        witness: Option<(F, F)>,
+    ) -> Result<FixedPoint<F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || format!("{:?}", op),
+            |mut region: Region<'_, F>| {
+                a.0.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let result = witness.map(|(result, _)| result);
+                let out = region.assign_advice(
+                    || "result",
+                    config.advice[2],
+                    0,
+                    || result.ok_or(Error::Synthesis),
+                )?;
+
+                match op {
+                    Op::Add | Op::Sub => {
+                        let selector = if op == Op::Add { config.s_add } else { config.s_sub };
+                        selector.enable(&mut region, 0)?;
+                        self.assign_range(&mut region, 1, result)?;
+                    }
+                    Op::Mul | Op::Div => {
+                        let selector = if op == Op::Mul { config.s_mul } else { config.s_div };
+                        selector.enable(&mut region, 0)?;
+
+                        let r = witness.map(|(_, r)| r);
+                        let bound = if op == Op::Mul {
+                            Some(F::from(config.scale))
+                        } else {
+                            b.0.value().copied()
+                        };
+                        let slack = bound.and_then(|bound| r.map(|r| bound - F::one() - r));
+
+                        self.assign_range(&mut region, 1, r)?;
+                        self.assign_range(&mut region, 2, slack)?;
+                        self.assign_range(&mut region, 3, result)?;
+                    }
+                }
+
+                Ok(FixedPoint(out))
+            },
+        )
+    }
+
+    fn honest_op(
+        &self,
+        layouter: impl Layouter<F>,
+        op: Op,
+        a: &FixedPoint<F>,
+        b: &FixedPoint<F>,
+    ) -> Result<FixedPoint<F>, Error> {
+        let witness = a.0.value().zip(b.0.value()).map(|(a, b)| self.witness(op, *a, *b)).transpose()?;
+        self.assign_op(layouter, op, a, b, witness)
+    }
+}
+
+impl<F: FieldExt, const N_BYTES: usize> FixedPointInstructions<F> for FixedPointChip<F, N_BYTES> {
+    fn load_private(&self, mut layouter: impl Layouter<F>, value: Option<u64>) -> Result<FixedPoint<F>, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| self.assign_range(&mut region, 0, value.map(F::from)).map(FixedPoint),
+        )
+    }
+
+    fn add(&self, layouter: impl Layouter<F>, a: &FixedPoint<F>, b: &FixedPoint<F>) -> Result<FixedPoint<F>, Error> {
+        self.honest_op(layouter, Op::Add, a, b)
+    }
+
+    fn sub(&self, layouter: impl Layouter<F>, a: &FixedPoint<F>, b: &FixedPoint<F>) -> Result<FixedPoint<F>, Error> {
+        self.honest_op(layouter, Op::Sub, a, b)
+    }
+
+    fn mul(&self, layouter: impl Layouter<F>, a: &FixedPoint<F>, b: &FixedPoint<F>) -> Result<FixedPoint<F>, Error> {
+        self.honest_op(layouter, Op::Mul, a, b)
+    }
+
+    fn div(&self, layouter: impl Layouter<F>, a: &FixedPoint<F>, b: &FixedPoint<F>) -> Result<FixedPoint<F>, Error> {
+        self.honest_op(layouter, Op::Div, a, b)
+    }
+
+    fn expose_public(&self, mut layouter: impl Layouter<F>, num: &FixedPoint<F>, row: usize) -> Result<(), Error> {
+        layouter.constrain_instance(num.0.cell(), self.config.instance, row)
+    }
+}
+
+/// 6 decimal places.
+const SCALE: u64 = 1_000_000;
+
+/// Native fixed-point arithmetic with the same rounding as the chip.
+fn native(op: Op, a: u64, b: u64, scale: u64) -> u64 {
+    let (a, b, scale) = (a as u128, b as u128, scale as u128);
+    (match op {
+        Op::Add => a + b,
+        Op::Sub => a - b,
+        Op::Mul => a * b / scale,
+        Op::Div => a * scale / b,
+    }) as u64
+}
+
+/// Proves the invoice `gross = price * qty`, `total = gross + shipping - discount`
+/// and `unit = total / count`, exposing `[gross, total, unit]`.
+#[derive(Default)]
+struct InvoiceCircuit<F> {
+    price: Option<u64>,
+    qty: Option<u64>,
+    shipping: Option<u64>,
+    discount: Option<u64>,
+    count: Option<u64>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for InvoiceCircuit<F> {
+    type Config = FixedPointConfig<F, 8>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        FixedPointChip::configure(meta, SCALE)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = FixedPointChip::construct(config);
+        chip.load_table(layouter.namespace(|| "byte table"))?;
+
+        let price = chip.load_private(layouter.namespace(|| "price"), self.price)?;
+        let qty = chip.load_private(layouter.namespace(|| "qty"), self.qty)?;
+        let shipping = chip.load_private(layouter.namespace(|| "shipping"), self.shipping)?;
+        let discount = chip.load_private(layouter.namespace(|| "discount"), self.discount)?;
+        let count = chip.load_private(layouter.namespace(|| "count"), self.count)?;
+
+        let gross = chip.mul(layouter.namespace(|| "price * qty"), &price, &qty)?;
+        let subtotal = chip.add(layouter.namespace(|| "gross + shipping"), &gross, &shipping)?;
+        let total = chip.sub(layouter.namespace(|| "subtotal - discount"), &subtotal, &discount)?;
+        let unit = chip.div(layouter.namespace(|| "total / count"), &total, &count)?;
+        chip.expose_public(layouter.namespace(|| "gross"), &gross, 0)?;
+        chip.expose_public(layouter.namespace(|| "total"), &total, 1)?;
+        chip.expose_public(layouter.namespace(|| "unit"), &unit, 2)
+    }
+}
+
+/// Runs one `op` with a forged witness that satisfies its gate: for `mul` and
+/// `div` the result is shifted by `-1` and the remainder moved up to
+/// compensate, so `r` is out of range; for `add` and `sub` the result is the
+/// field's, which wraps around when `a - b` is negative.
+struct TamperCircuit<F> {
+    op: Op,
+    a: u64,
+    b: u64,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for TamperCircuit<F> {
+    type Config = FixedPointConfig<F, 8>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            op: self.op,
+            a: 0,
+            b: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        FixedPointChip::configure(meta, SCALE)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = FixedPointChip::construct(config);
+        chip.load_table(layouter.namespace(|| "byte table"))?;
+
+        let a = chip.load_private(layouter.namespace(|| "a"), Some(self.a))?;
+        let b = chip.load_private(layouter.namespace(|| "b"), Some(self.b))?;
+
+        let (q, r) = chip.witness(self.op, F::from(self.a), F::from(self.b))?;
+        let witness = match self.op {
+            Op::Add | Op::Sub => (q, r),
+            Op::Mul => (q - F::one(), r + F::from(SCALE)),
+            Op::Div => (q - F::one(), r + F::from(self.b)),
+        };
+        let out = chip.assign_op(layouter.namespace(|| "tampered"), self.op, &a, &b, Some(witness))?;
+        chip.expose_public(layouter.namespace(|| "out"), &out, 0)
+    }
+}
+
+fn invoice_test() {
+    let k = 9;
+
+    // 12.345678 * 3.5 + 4.99 - 1.25, split 7 ways.
+    let price = 12_345_678;
+    let qty = 3_500_000;
+    let shipping = 4_990_000;
+    let discount = 1_250_000;
+    let count = 7_000_000;
+
+    let gross = native(Op::Mul, price, qty, SCALE);
+    let subtotal = native(Op::Add, gross, shipping, SCALE);
+    let total = native(Op::Sub, subtotal, discount, SCALE);
+    let unit = native(Op::Div, total, count, SCALE);
+    assert_eq!(gross, 43_209_873);
+    assert_eq!(unit, 6_707_124);
+
+    let circuit = InvoiceCircuit::<Fp> {
+        price: Some(price),
+        qty: Some(qty),
+        shipping: Some(shipping),
+        discount: Some(discount),
+        count: Some(count),
+        _marker: PhantomData,
+    };
+    let public_inputs = vec![Fp::from(gross), Fp::from(total), Fp::from(unit)];
+    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    println!("{}", prover::round_trip(k, &circuit, &[public_inputs.clone()]).unwrap());
+
+    // A result rounded the other way is rejected.
+    let mut bad_inputs = public_inputs;
+    bad_inputs[2] += Fp::one();
+    let prover = MockProver::run(k, &circuit, vec![bad_inputs]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // A discount larger than the subtotal underflows, and the division
+    // has no integer to work with. `tamper_test` proves the wrapped total
+    // itself is rejected.
+    let circuit = InvoiceCircuit::<Fp> {
+        discount: Some(subtotal + 1),
+        ..circuit
+    };
+    let public_inputs = vec![Fp::from(gross), -Fp::one(), Fp::zero()];
+    assert!(MockProver::run(k, &circuit, vec![public_inputs]).is_err());
+
+    // Dividing by zero is rejected.
+    let circuit = InvoiceCircuit::<Fp> {
+        discount: Some(discount),
+        count: Some(0),
+        ..circuit
+    };
+    let public_inputs = vec![Fp::from(gross), Fp::from(total), Fp::zero()];
+    let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+fn tamper_test() {
+    let k = 9;
+    let a = 2_500_000;
+    let b = 1_300_000;
+
+    for op in [Op::Mul, Op::Div] {
+        let circuit = TamperCircuit::<Fp> {
+            op,
+            a,
+            b,
+            _marker: PhantomData,
+        };
+        let public_inputs = vec![Fp::from(native(op, a, b, SCALE) - 1)];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err(), "{:?}", op);
+    }
+
+    // `b - a` satisfies the sub gate, but it wraps around the field and only
+    // the range check on the result rejects it.
+    for (a, b) in [(a, b), (b, a)] {
+        let circuit = TamperCircuit::<Fp> {
+            op: Op::Sub,
+            a,
+            b,
+            _marker: PhantomData,
+        };
+        let public_inputs = vec![Fp::from(a) - Fp::from(b)];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify().is_ok(), a >= b, "{} - {}", a, b);
+    }
+}
+
+fn main() {
+    println!("# Fixed-point invoice.");
+    invoice_test();
+    println!("# Out-of-range remainders.");
+    tamper_test();
+    println!("# Done.");
+}
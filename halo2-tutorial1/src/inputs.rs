@@ -0,0 +1,340 @@
+#![allow(dead_code)]
+
+//! JSON schemas for the private inputs of the circuits the `cli` binary runs.
+//! Public inputs are never part of a schema: each is computed from the
+//! private inputs with the circuit's native reference function.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use ff::PrimeField;
+use halo2_proofs::{pairing::bn256::Fr, plonk::Circuit};
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use halo2_tutorial::convert::{to_u64, ConversionError};
+use halo2_tutorial::table;
+
+use crate::{fibo, fibo_rows, fibo_xor, select, sort};
+
+/// Why a set of inputs does not describe a provable statement.
+#[derive(Debug)]
+pub enum InputError {
+    Json(serde_json::Error),
+    /// An input needed by a reference function does not fit its integer type.
+    Conversion(ConversionError),
+    /// The inputs are well formed but the circuit cannot prove them.
+    Invalid(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Json(err) => write!(f, "bad json: {}", err),
+            InputError::Conversion(err) => write!(f, "{}", err),
+            InputError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<serde_json::Error> for InputError {
+    fn from(err: serde_json::Error) -> Self {
+        InputError::Json(err)
+    }
+}
+
+impl From<ConversionError> for InputError {
+    fn from(err: ConversionError) -> Self {
+        InputError::Conversion(err)
+    }
+}
+
+/// A field element, read from a JSON number, a decimal string or a `0x` hex
+/// string, and written as 64 hex digits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Field(pub Fr);
+
+impl Field {
+    /// Parses `s` as decimal, or as hex with a `0x` prefix. Values of the
+    /// modulus or above are rejected rather than reduced.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (digits, radix) = match s.strip_prefix("0x") {
+            Some(digits) => (digits, 16),
+            None => (s, 10),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        for digit in digits.chars() {
+            // Multiply the little-endian bytes by the radix and add the digit.
+            let mut carry = digit.to_digit(radix)?;
+            for byte in repr.as_mut().iter_mut() {
+                let value = *byte as u32 * radix + carry;
+                *byte = value as u8;
+                carry = value >> 8;
+            }
+            if carry != 0 {
+                return None;
+            }
+        }
+        Option::from(Fr::from_repr(repr)).map(Field)
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x")?;
+        for byte in self.0.to_repr().as_ref().iter().rev() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Field {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldVisitor;
+
+        impl<'de> Visitor<'de> for FieldVisitor {
+            type Value = Field;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a field element as a number, a decimal string or a 0x hex string")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Field, E> {
+                Ok(Field(Fr::from(value)))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Field, E> {
+                Field::parse(value).ok_or_else(|| E::custom(format!("{} is not a field element", value)))
+            }
+        }
+
+        deserializer.deserialize_any(FieldVisitor)
+    }
+}
+
+/// The private inputs of a circuit.
+pub(crate) trait Inputs: DeserializeOwned {
+    type Circuit: Circuit<Fr>;
+
+    /// The circuit with its witnesses filled in.
+    fn circuit(&self) -> Result<Self::Circuit, InputError>;
+
+    /// The public inputs the circuit exposes, one vector per instance column.
+    fn instances(&self) -> Result<Vec<Vec<Fr>>, InputError>;
+}
+
+/// Reads the private inputs `I` from `json` and returns the circuit with the
+/// public inputs it should verify against.
+pub(crate) fn from_json<I: Inputs>(json: &[u8]) -> Result<(I::Circuit, Vec<Vec<Fr>>), InputError> {
+    let inputs: I = serde_json::from_slice(json)?;
+    Ok((inputs.circuit()?, inputs.instances()?))
+}
+
+/// The public inputs as JSON, one array of field elements per instance column.
+pub fn instances_to_json(instances: &[Vec<Fr>]) -> Result<String, InputError> {
+    let columns: Vec<Vec<Field>> = instances.iter().map(|column| column.iter().copied().map(Field).collect()).collect();
+    Ok(serde_json::to_string_pretty(&columns)?)
+}
+
+pub fn instances_from_json(json: &[u8]) -> Result<Vec<Vec<Fr>>, InputError> {
+    let columns: Vec<Vec<Field>> = serde_json::from_slice(json)?;
+    Ok(columns.into_iter().map(|column| column.into_iter().map(|value| value.0).collect()).collect())
+}
+
+fn overflow(name: &str) -> InputError {
+    InputError::Invalid(format!("{} overflows a u64", name))
+}
+
+/// `fibo` and `fibo-rows`: the first two terms and the length of the sequence.
+#[derive(Clone, Debug, Deserialize)]
+pub struct FiboInputs {
+    pub a: Field,
+    pub b: Field,
+    pub num: usize,
+}
+
+impl FiboInputs {
+    /// The `num`th term of the sequence, which needs at least 3 terms.
+    fn last_term(&self, num: usize) -> Result<Fr, InputError> {
+        if self.num < 3 {
+            return Err(InputError::Invalid(format!("num is {}, the sequence needs at least 3 terms", self.num)));
+        }
+        let (a, b) = (to_u64(&self.a.0, "a")?, to_u64(&self.b.0, "b")?);
+        let seq = fibo::get_fibo_seq(a, b, num).ok_or_else(|| overflow("the sequence"))?;
+        Ok(Fr::from(seq[num - 1]))
+    }
+}
+
+impl Inputs for FiboInputs {
+    type Circuit = fibo::FiboCircuit<Fr>;
+
+    fn circuit(&self) -> Result<Self::Circuit, InputError> {
+        Ok(fibo::FiboCircuit {
+            a: Some(self.a.0),
+            b: Some(self.b.0),
+            num: self.num,
+        })
+    }
+
+    fn instances(&self) -> Result<Vec<Vec<Fr>>, InputError> {
+        Ok(vec![vec![self.last_term(self.num)?]])
+    }
+}
+
+/// Same inputs as `fibo`, laid out two terms per row.
+#[derive(Clone, Debug, Deserialize)]
+pub struct FiboRowsInputs(pub FiboInputs);
+
+impl Inputs for FiboRowsInputs {
+    type Circuit = fibo_rows::FiboCircuit<Fr>;
+
+    fn circuit(&self) -> Result<Self::Circuit, InputError> {
+        Ok(fibo_rows::FiboCircuit {
+            a: Some(self.0.a.0),
+            b: Some(self.0.b.0),
+            num: self.0.num,
+        })
+    }
+
+    fn instances(&self) -> Result<Vec<Vec<Fr>>, InputError> {
+        // Rows hold two terms each, so an odd `num` runs one term further.
+        let num = (self.0.num + 1) / 2 * 2;
+        Ok(vec![vec![self.0.last_term(num)?]])
+    }
+}
+
+/// `fibo-xor`: the first three terms and the length of the sequence.
+#[derive(Clone, Debug, Deserialize)]
+pub struct FiboXorInputs {
+    pub a: Field,
+    pub b: Field,
+    pub c: Field,
+    pub num: usize,
+}
+
+/// The xor lookup table covers 5-bit operands.
+const XOR_BITS: u32 = 5;
+
+impl Inputs for FiboXorInputs {
+    type Circuit = fibo_xor::FiboCircuit<Fr>;
+
+    fn circuit(&self) -> Result<Self::Circuit, InputError> {
+        Ok(fibo_xor::FiboCircuit {
+            a: Some(self.a.0),
+            b: Some(self.b.0),
+            c: Some(self.c.0),
+            num: self.num,
+        })
+    }
+
+    /// The circuit exposes nothing, but the sequence is still checked to stay
+    /// within the xor table.
+    fn instances(&self) -> Result<Vec<Vec<Fr>>, InputError> {
+        if self.num < 3 {
+            return Err(InputError::Invalid(format!("num is {}, the sequence needs at least 3 terms", self.num)));
+        }
+        let (a, b, c) = (to_u64(&self.a.0, "a")?, to_u64(&self.b.0, "b")?, to_u64(&self.c.0, "c")?);
+        let seq = fibo_xor::get_sequence(a, b, c, self.num).ok_or_else(|| overflow("the sequence"))?;
+        // Each step after the first row xors the two terms before it.
+        let operands = if self.num > 3 { &seq[1..self.num - 1] } else { &[] };
+        if let Some(term) = operands.iter().find(|term| **term >> XOR_BITS != 0) {
+            return Err(InputError::Invalid(format!("term {} does not fit the {}-bit xor table", term, XOR_BITS)));
+        }
+        Ok(vec![])
+    }
+}
+
+/// `sort`: the values to sort. The comparisons between adjacent sorted values
+/// are derived with `bubble_sort`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SortInputs {
+    pub values: Vec<Field>,
+}
+
+impl SortInputs {
+    fn values(&self) -> Result<Vec<u64>, InputError> {
+        if self.values.is_empty() {
+            return Err(InputError::Invalid("there are no values to sort".to_string()));
+        }
+        Ok(self.values.iter().map(|value| to_u64(&value.0, "value")).collect::<Result<_, _>>()?)
+    }
+}
+
+impl Inputs for SortInputs {
+    type Circuit = sort::TestCircuit<Fr>;
+
+    fn circuit(&self) -> Result<Self::Circuit, InputError> {
+        let values = self.values()?;
+        let sorted = sort::bubble_sort(&values);
+        Ok(sort::TestCircuit {
+            checks: sorted.windows(2).map(|pair| Some(pair[0] < pair[1])).collect(),
+            values: values.into_iter().map(Some).collect(),
+            _marker: PhantomData,
+        })
+    }
+
+    fn instances(&self) -> Result<Vec<Vec<Fr>>, InputError> {
+        self.values()?;
+        Ok(vec![])
+    }
+}
+
+/// `range-table`: the value to look up.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RangeTableInputs {
+    pub value: Field,
+}
+
+impl Inputs for RangeTableInputs {
+    type Circuit = table::MyCircuit<Fr>;
+
+    fn circuit(&self) -> Result<Self::Circuit, InputError> {
+        Ok(table::MyCircuit { value: Some(self.value.0) })
+    }
+
+    fn instances(&self) -> Result<Vec<Vec<Fr>>, InputError> {
+        Ok(vec![])
+    }
+}
+
+/// `select`: a boolean `cond` choosing between `a` and `b`, which may also be
+/// given as `thenval` and `elseval`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SelectInputs {
+    pub cond: Field,
+    #[serde(alias = "thenval")]
+    pub a: Field,
+    #[serde(alias = "elseval")]
+    pub b: Field,
+}
+
+impl Inputs for SelectInputs {
+    type Circuit = select::SelectCircuit<Fr>;
+
+    fn circuit(&self) -> Result<Self::Circuit, InputError> {
+        Ok(select::SelectCircuit {
+            cond: Some(self.cond.0),
+            a: Some(self.a.0),
+            b: Some(self.b.0),
+        })
+    }
+
+    fn instances(&self) -> Result<Vec<Vec<Fr>>, InputError> {
+        let out = match to_u64(&self.cond.0, "cond")? {
+            1 => self.a.0,
+            0 => self.b.0,
+            cond => return Err(InputError::Invalid(format!("cond is {}, not a boolean", cond))),
+        };
+        Ok(vec![vec![out]])
+    }
+}
@@ -0,0 +1,422 @@
+use halo2_tutorial::{convert, expr, lt, prover, table};
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+
+use convert::{synthesis_error, to_u128};
+use lt::{LtChip, LtConfig, LtInstruction};
+use table::RangTableConfig;
+
+trait DivRemInstructions<F: FieldExt>: Chip<F> {
+    /// Loads a number into the circuit as a private input.
+    fn load_private(&self, layouter: impl Layouter<F>, value: Option<F>) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Returns `(q, r)` with `a = q * b + r` and `0 <= r < b`. The circuit is
+    /// unsatisfiable if `b` is zero.
+    fn div_rem(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error>;
+
+    /// Returns `a / b`, rounded down.
+    fn floor_div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.div_rem(layouter, a, b).map(|(q, _)| q)
+    }
+
+    /// Returns `a mod b`.
+    fn modulo(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.div_rem(layouter, a, b).map(|(_, r)| r)
+    }
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+/// Config for the DivRem chip. `q`, `r` and `b` are range checked to
+/// `N_BYTES` bytes, so `q * b + r` never wraps around the field.
+#[derive(Clone, Debug)]
+struct DivRemConfig<F: FieldExt, const N_BYTES: usize> {
+    /// `a`, `b`, `q` and `r`.
+    advice: [Column<Advice>; 4],
+    /// The value being range checked on each `s_range` row.
+    value: Column<Advice>,
+    /// Little-endian bytes of `value`.
+    bytes: [Column<Advice>; N_BYTES],
+    instance: Column<Instance>,
+    s_div_rem: Selector,
+    s_range: Selector,
+    /// Proves `r < b`.
+    lt: LtConfig<F, N_BYTES>,
+    table: RangTableConfig<F>,
+}
+
+struct DivRemChip<F: FieldExt, const N_BYTES: usize> {
+    config: DivRemConfig<F, N_BYTES>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> Chip<F> for DivRemChip<F, N_BYTES> {
+    type Config = DivRemConfig<F, N_BYTES>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const N_BYTES: usize> DivRemChip<F, N_BYTES> {
+    fn construct(config: DivRemConfig<F, N_BYTES>) -> Self {
+        Self { config }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> DivRemConfig<F, N_BYTES> {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let value = meta.advice_column();
+        let bytes = [(); N_BYTES].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let s_div_rem = meta.complex_selector();
+        let s_range = meta.complex_selector();
+        let table = RangTableConfig::configure(meta);
+
+        meta.enable_equality(instance);
+        for col in &advice {
+            meta.enable_equality(*col);
+        }
+
+        let [a, b, q, r] = advice;
+        let lt = LtChip::configure(
+            meta,
+            |meta| meta.query_selector(s_div_rem),
+            |meta| meta.query_advice(r, Rotation::cur()),
+            |meta| meta.query_advice(b, Rotation::cur()),
+        );
+
+        // |  a  |  b  |  q  |  r  | lt, diff | value | bytes | s_div_rem | s_range |
+        // |-----|-----|-----|-----|----------|-------|-------|-----------|---------|
+        // |  a  |  b  |  q  |  r  | r < b    |   q   |  ...  |     1     |    1    |
+        // |     |     |     |     |          |   r   |  ...  |           |    1    |
+        // |     |     |     |     |          |   b   |  ...  |           |    1    |
+        meta.create_gate("div_rem", |meta| {
+            let s = meta.query_selector(s_div_rem);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let q = meta.query_advice(q, Rotation::cur());
+            let r = meta.query_advice(r, Rotation::cur());
+            let q_range = meta.query_advice(value, Rotation::cur());
+            let r_range = meta.query_advice(value, Rotation::next());
+            let b_range = meta.query_advice(value, Rotation(2));
+            let is_lt = lt.is_lt(meta, None);
+
+            [
+                a - q.clone() * b.clone() - r.clone(),
+                is_lt - Expression::Constant(F::one()),
+                q_range - q,
+                r_range - r,
+                b_range - b,
+            ]
+            .into_iter()
+            .map(move |poly| s.clone() * poly)
+        });
+
+        meta.create_gate("range", |meta| {
+            let s = meta.query_selector(s_range);
+            let v = meta.query_advice(value, Rotation::cur());
+            let bytes = bytes
+                .iter()
+                .map(|c| meta.query_advice(*c, Rotation::cur()))
+                .collect::<Vec<Expression<F>>>();
+            vec![s * (v - expr::expr_from_bytes(&bytes))]
+        });
+
+        // The Lt chip leaves its difference bytes unchecked, so they go through
+        // the same table as the range-checked values.
+        for byte in bytes {
+            meta.lookup("value byte", |meta| {
+                let s = meta.query_selector(s_range);
+                vec![(s * meta.query_advice(byte, Rotation::cur()), table.col_value)]
+            });
+        }
+        for byte in lt.diff {
+            meta.lookup("lt diff byte", |meta| {
+                let s = meta.query_selector(s_div_rem);
+                vec![(s * meta.query_advice(byte, Rotation::cur()), table.col_value)]
+            });
+        }
+
+        DivRemConfig {
+            advice,
+            value,
+            bytes,
+            instance,
+            s_div_rem,
+            s_range,
+            lt,
+            table,
+        }
+    }
+
+    fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.config.table.load(&mut layouter, (0..256).collect())
+    }
+
+    fn assign_range(&self, region: &mut Region<'_, F>, offset: usize, value: Option<F>) -> Result<(), Error> {
+        let config = self.config();
+        config.s_range.enable(region, offset)?;
+        region.assign_advice(|| "value", config.value, offset, || value.ok_or(Error::Synthesis))?;
+
+        let repr = value.map(|value| value.to_repr());
+        for (idx, column) in config.bytes.iter().enumerate() {
+            region.assign_advice(
+                || format!("byte {}", idx),
+                *column,
+                offset,
+                || {
+                    repr.as_ref()
+                        .map(|repr| F::from(repr.as_ref()[idx] as u64))
+                        .ok_or(Error::Synthesis)
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Assigns a `div_rem` region with `(q, r)` as the claimed witness.
+    fn assign_div_rem(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        q: Option<F>,
+        r: Option<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let config = self.config();
+        let lt_chip = LtChip::construct(config.lt);
+        let b_value = b.value().copied();
+
+        layouter.assign_region(
+            || "div_rem",
+            |mut region: Region<'_, F>| {
+                config.s_div_rem.enable(&mut region, 0)?;
+
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let q_cell = region.assign_advice(|| "q", config.advice[2], 0, || q.ok_or(Error::Synthesis))?;
+                let r_cell = region.assign_advice(|| "r", config.advice[3], 0, || r.ok_or(Error::Synthesis))?;
+
+                lt_chip.assign(&mut region, 0, r, b_value)?;
+                self.assign_range(&mut region, 0, q)?;
+                self.assign_range(&mut region, 1, r)?;
+                self.assign_range(&mut region, 2, b_value)?;
+
+                Ok((q_cell, r_cell))
+            },
+        )
+    }
+}
+
+impl<F: FieldExt, const N_BYTES: usize> DivRemInstructions<F> for DivRemChip<F, N_BYTES> {
+    fn load_private(&self, mut layouter: impl Layouter<F>, value: Option<F>) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "load private",
+            |mut region| region.assign_advice(|| "private input", config.advice[0], 0, || value.ok_or(Error::Synthesis)),
+        )
+    }
+
+    fn div_rem(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let witness = a
+            .value()
+            .zip(b.value())
+            .map(|(a, b)| {
+                let a_value = to_u128(a, "a").map_err(synthesis_error)?;
+                let b_value = to_u128(b, "b").map_err(synthesis_error)?;
+
+                // There is no valid witness for a zero divisor; `(0, a)` fails `r < b`.
+                let (q, r) = match (a_value.checked_div(b_value), a_value.checked_rem(b_value)) {
+                    (Some(q), Some(r)) => (q, r),
+                    _ => (0, a_value),
+                };
+                Ok::<_, Error>((F::from_u128(q), F::from_u128(r)))
+            })
+            .transpose()?;
+        self.assign_div_rem(layouter, a, b, witness.map(|(q, _)| q), witness.map(|(_, r)| r))
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+/// Proves `q = a / b` and `r = a mod b`, exposing `[q, r]`. A non-zero
+/// `shift` replaces the honest witness with `(q + shift, r - shift * b)`,
+/// which still satisfies `a = q * b + r`.
+#[derive(Default)]
+struct DivRemCircuit<F> {
+    a: Option<u64>,
+    b: Option<u64>,
+    shift: i64,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for DivRemCircuit<F> {
+    type Config = DivRemConfig<F, 8>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // The shift picks the layout, so it is kept.
+        Self {
+            shift: self.shift,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        DivRemChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = DivRemChip::construct(config);
+        chip.load_table(layouter.namespace(|| "byte table"))?;
+
+        let a = chip.load_private(layouter.namespace(|| "load a"), self.a.map(F::from))?;
+        let b = chip.load_private(layouter.namespace(|| "load b"), self.b.map(F::from))?;
+
+        let (q, r) = if self.shift == 0 {
+            chip.div_rem(layouter.namespace(|| "a / b"), &a, &b)?
+        } else {
+            let shift = F::from(self.shift.unsigned_abs()) * if self.shift < 0 { -F::one() } else { F::one() };
+            let q = self.a.zip(self.b).map(|(a, b)| F::from(a / b) + shift);
+            let r = self.a.zip(self.b).map(|(a, b)| F::from(a % b) - shift * F::from(b));
+            chip.assign_div_rem(layouter.namespace(|| "a / b"), &a, &b, q, r)?
+        };
+
+        chip.expose_public(layouter.namespace(|| "expose q"), &q, 0)?;
+        chip.expose_public(layouter.namespace(|| "expose r"), &r, 1)
+    }
+}
+
+/// Proves `a / b`, or `a mod b` if `modulo` is set, exposing only that.
+#[derive(Default)]
+struct OneOutputCircuit<F> {
+    a: Option<u64>,
+    b: Option<u64>,
+    modulo: bool,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for OneOutputCircuit<F> {
+    type Config = DivRemConfig<F, 8>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            modulo: self.modulo,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        DivRemChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = DivRemChip::construct(config);
+        chip.load_table(layouter.namespace(|| "byte table"))?;
+
+        let a = chip.load_private(layouter.namespace(|| "load a"), self.a.map(F::from))?;
+        let b = chip.load_private(layouter.namespace(|| "load b"), self.b.map(F::from))?;
+        let out = if self.modulo {
+            chip.modulo(layouter.namespace(|| "a mod b"), &a, &b)?
+        } else {
+            chip.floor_div(layouter.namespace(|| "a / b"), &a, &b)?
+        };
+        chip.expose_public(layouter.namespace(|| "expose out"), &out, 0)
+    }
+}
+
+fn main() {
+    let k = 9;
+
+    for (a, b) in [(100u64, 7u64), (5, 9), (63, 9), ((1 << 40) + 3, 1000), (u64::MAX, 1 << 32)] {
+        let circuit = DivRemCircuit::<Fp> { a: Some(a), b: Some(b), ..Default::default() };
+        let public_inputs = vec![Fp::from(a / b), Fp::from(a % b)];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{} / {}", a, b);
+
+        // A wrong quotient is rejected.
+        let mut bad_inputs = public_inputs;
+        bad_inputs[0] += Fp::one();
+        let prover = MockProver::run(k, &circuit, vec![bad_inputs]).unwrap();
+        assert!(prover.verify().is_err(), "{} / {}", a, b);
+    }
+
+    let circuit = DivRemCircuit::<Fp> { a: Some(100), b: Some(7), ..Default::default() };
+    println!("{}", prover::round_trip(k, &circuit, &[vec![Fp::from(14), Fp::from(2)]]).unwrap());
+
+    // Shifting the quotient keeps `a = q * b + r`, but pushes `r` out of `[0, b)`.
+    let (a, b) = (100u64, 7u64);
+    for shift in [-1i64, 1] {
+        let circuit = DivRemCircuit::<Fp> { a: Some(a), b: Some(b), shift, ..Default::default() };
+        let q = Fp::from(a / b) + if shift < 0 { -Fp::one() } else { Fp::one() };
+        let r = Fp::from(a) - q * Fp::from(b);
+        let prover = MockProver::run(k, &circuit, vec![vec![q, r]]).unwrap();
+        assert!(prover.verify().is_err(), "shift {}", shift);
+    }
+
+    // Dividing by zero is rejected.
+    let circuit = DivRemCircuit::<Fp> { a: Some(a), b: Some(0), ..Default::default() };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero(), Fp::from(a)]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // floor_div and modulo each expose one side of the same region.
+    for (modulo, out) in [(false, a / b), (true, a % b)] {
+        let circuit = OneOutputCircuit::<Fp> { a: Some(a), b: Some(b), modulo, ..Default::default() };
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(out)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "modulo {}", modulo);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(out + 1)]]).unwrap();
+        assert!(prover.verify().is_err(), "modulo {}", modulo);
+    }
+}
@@ -0,0 +1,268 @@
+#![allow(dead_code)]
+
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::{Coordinates, CurveAffine},
+    pairing::group::ff::PrimeField,
+    transcript::{Challenge255, EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite},
+};
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation of lane `x + 5 * y` in the rho step.
+const ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27, //
+    36, 44, 6, 55, 20, //
+    3, 10, 43, 25, 39, //
+    41, 45, 15, 21, 8, //
+    18, 2, 61, 56, 14,
+];
+
+/// Bytes absorbed per permutation by Keccak-256.
+const RATE: usize = 136;
+
+fn keccak_f(a: &mut [u64; 25]) {
+    for rc in ROUND_CONSTANTS.iter() {
+        // theta
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho and pi
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = a[x + 5 * y].rotate_left(ROTATIONS[x + 5 * y]);
+            }
+        }
+
+        // chi
+        for x in 0..5 {
+            for y in 0..5 {
+                a[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+
+        // iota
+        a[0] ^= rc;
+    }
+}
+
+/// The Keccak-256 hash of `data`, as computed by the EVM's `KECCAK256`.
+/// This is the original Keccak padding, not the SHA3-256 one.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    padded.resize((padded.len() + RATE - 1) / RATE * RATE, 0);
+    *padded.last_mut().unwrap() |= 0x80;
+
+    let mut state = [0u64; 25];
+    for block in padded.chunks(RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(bytes);
+            *lane ^= u64::from_le_bytes(word);
+        }
+        keccak_f(&mut state);
+    }
+
+    let mut out = [0u8; 32];
+    for (bytes, lane) in out.chunks_mut(8).zip(state.iter()) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    out
+}
+
+/// Big-endian bytes of a field element, as the EVM stores a `uint256`.
+fn to_be_bytes<F: PrimeField>(value: &F) -> Vec<u8> {
+    value.to_repr().as_ref().iter().rev().copied().collect()
+}
+
+fn from_be_bytes<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let mut repr = F::Repr::default();
+    for (byte, be) in repr.as_mut().iter_mut().zip(bytes.iter().rev()) {
+        *byte = *be;
+    }
+    Option::from(F::from_repr(repr))
+}
+
+/// A point as the EVM precompiles take it: `x` then `y`, with the identity
+/// as `(0, 0)`.
+fn point_to_bytes<C: CurveAffine>(point: &C) -> Vec<u8> {
+    let coords: Option<Coordinates<C>> = point.coordinates().into();
+    match coords {
+        Some(coords) => [to_be_bytes(coords.x()), to_be_bytes(coords.y())].concat(),
+        None => vec![0; 64],
+    }
+}
+
+fn point_from_bytes<C: CurveAffine>(bytes: &[u8]) -> Option<C> {
+    if bytes.iter().all(|byte| *byte == 0) {
+        return Some(C::identity());
+    }
+    let (x, y) = bytes.split_at(32);
+    Option::from(C::from_xy(from_be_bytes(x)?, from_be_bytes(y)?))
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, msg)
+}
+
+/// The Fiat-Shamir state a Solidity verifier keeps: everything absorbed since
+/// the last challenge, after that challenge's hash.
+#[derive(Clone, Debug, Default)]
+struct KeccakState {
+    buffer: Vec<u8>,
+}
+
+impl KeccakState {
+    /// Hashes the buffer into a challenge, `uint256(keccak256(buffer)) mod r`,
+    /// and restarts the buffer from the hash.
+    fn squeeze<C: CurveAffine>(&mut self) -> Challenge255<C> {
+        let hash = keccak256(&self.buffer);
+        self.buffer = hash.to_vec();
+        // `Challenge255` reduces 64 little-endian bytes.
+        let mut wide = [0u8; 64];
+        for (byte, be) in wide.iter_mut().zip(hash.iter().rev()) {
+            *byte = *be;
+        }
+        Challenge255::<C>::new(&wide)
+    }
+}
+
+/// A transcript writer hashing with Keccak-256 over EVM encoded points and
+/// scalars, so that a Solidity verifier can replay it.
+#[derive(Clone, Debug)]
+pub struct Keccak256Write<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
+    state: KeccakState,
+    writer: W,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Keccak256Write<W, C, E> {
+    pub fn init(writer: W) -> Self {
+        Self {
+            state: KeccakState::default(),
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn finalize(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>> for Keccak256Write<W, C, Challenge255<C>> {
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.state.squeeze()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.state.buffer.extend(point_to_bytes(&point));
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.state.buffer.extend(to_be_bytes(&scalar));
+        Ok(())
+    }
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>> for Keccak256Write<W, C, Challenge255<C>> {
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        self.writer.write_all(&point_to_bytes(&point))
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        self.writer.write_all(&to_be_bytes(&scalar))
+    }
+}
+
+/// Reads proofs written by `Keccak256Write`.
+#[derive(Clone, Debug)]
+pub struct Keccak256Read<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
+    state: KeccakState,
+    reader: R,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>> Keccak256Read<R, C, E> {
+    pub fn init(reader: R) -> Self {
+        Self {
+            state: KeccakState::default(),
+            reader,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>> for Keccak256Read<R, C, Challenge255<C>> {
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.state.squeeze()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.state.buffer.extend(point_to_bytes(&point));
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.state.buffer.extend(to_be_bytes(&scalar));
+        Ok(())
+    }
+}
+
+impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>> for Keccak256Read<R, C, Challenge255<C>> {
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut bytes = [0u8; 64];
+        self.reader.read_exact(&mut bytes)?;
+        let point = point_from_bytes(&bytes).ok_or_else(|| invalid("invalid point encoding in proof"))?;
+        self.common_point(point)?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut bytes = [0u8; 32];
+        self.reader.read_exact(&mut bytes)?;
+        let scalar = from_be_bytes(&bytes).ok_or_else(|| invalid("invalid field element encoding in proof"))?;
+        self.common_scalar(scalar)?;
+        Ok(scalar)
+    }
+}
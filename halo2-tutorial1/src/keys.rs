@@ -0,0 +1,148 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use halo2_proofs::{
+    pairing::bn256::{Fr, G1Affine},
+    plonk::{keygen_pk, keygen_vk, Circuit, ConstraintSystem, Error, ProvingKey, VerifyingKey},
+    poly::commitment::Params,
+};
+
+/// Tags the verifying key files written by `write_vk`.
+const VK_MAGIC: &[u8; 4] = b"H2VK";
+
+/// Why a parameter or key file could not be used.
+#[derive(Debug)]
+pub enum KeyError {
+    Io(io::Error),
+    /// The file does not start with the expected tag.
+    NotAKeyFile,
+    /// The file was generated for circuits of `2^found` rows.
+    WrongK { found: u32, expected: u32 },
+    /// The file was generated for a circuit with a different shape.
+    WrongShape,
+    Synthesis(Error),
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::Io(err) => write!(f, "i/o error: {}", err),
+            KeyError::NotAKeyFile => write!(f, "not a verifying key file"),
+            KeyError::WrongK { found, expected } => write!(f, "generated for k = {}, expected k = {}", found, expected),
+            KeyError::WrongShape => write!(f, "generated for a different circuit shape"),
+            KeyError::Synthesis(err) => write!(f, "keygen failed: {:?}", err),
+        }
+    }
+}
+
+impl From<io::Error> for KeyError {
+    fn from(err: io::Error) -> Self {
+        KeyError::Io(err)
+    }
+}
+
+impl From<Error> for KeyError {
+    fn from(err: Error) -> Self {
+        KeyError::Synthesis(err)
+    }
+}
+
+/// A fingerprint of the gates, lookups and columns `C` configures. It does
+/// not cover fixed column values, which `regenerate_pk` checks instead.
+///
+/// It hashes the `Debug` output of the pinned constraint system, which halo2
+/// does not promise to keep stable. The fingerprint only holds for a fixed
+/// halo2 version, and another version may reject key files as `WrongShape`.
+pub fn shape<C: Circuit<Fr>>() -> u64 {
+    let mut meta = ConstraintSystem::<Fr>::default();
+    C::configure(&mut meta);
+    // 64-bit FNV-1a, which unlike `DefaultHasher` does not change with the
+    // Rust release.
+    format!("{:?}", meta.pinned())
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Writes `params` to `path`. The file records its own `k`.
+pub fn write_params(path: &Path, params: &Params<G1Affine>) -> Result<(), KeyError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    params.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads parameters for circuits of `2^k` rows from `path`.
+pub fn read_params(path: &Path, k: u32) -> Result<Params<G1Affine>, KeyError> {
+    let params = Params::<G1Affine>::read(&mut BufReader::new(File::open(path)?))?;
+    if params.k != k {
+        return Err(KeyError::WrongK {
+            found: params.k,
+            expected: k,
+        });
+    }
+    Ok(params)
+}
+
+/// Writes `vk` to `path`, behind a header recording `k` and the shape of
+/// `C`, which `VerifyingKey::read` would otherwise take on trust.
+pub fn write_vk<C: Circuit<Fr>>(path: &Path, k: u32, vk: &VerifyingKey<G1Affine>) -> Result<(), KeyError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(VK_MAGIC)?;
+    writer.write_all(&k.to_le_bytes())?;
+    writer.write_all(&shape::<C>().to_le_bytes())?;
+    vk.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a verifying key for `C` from `path`, checking it was written for
+/// `params` and for the shape of `C`.
+pub fn read_vk<C: Circuit<Fr>>(path: &Path, params: &Params<G1Affine>) -> Result<VerifyingKey<G1Affine>, KeyError> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != VK_MAGIC {
+        return Err(KeyError::NotAKeyFile);
+    }
+
+    let mut k = [0u8; 4];
+    reader.read_exact(&mut k)?;
+    let k = u32::from_le_bytes(k);
+    if k != params.k {
+        return Err(KeyError::WrongK {
+            found: k,
+            expected: params.k,
+        });
+    }
+
+    let mut fingerprint = [0u8; 8];
+    reader.read_exact(&mut fingerprint)?;
+    if u64::from_le_bytes(fingerprint) != shape::<C>() {
+        return Err(KeyError::WrongShape);
+    }
+
+    Ok(VerifyingKey::read::<_, C>(&mut reader, params)?)
+}
+
+/// Regenerates the proving key for `vk` from `circuit.without_witnesses()`.
+/// Keygen is deterministic, so the circuit must yield `vk` byte for byte;
+/// anything else, such as a different number of rows, is a shape mismatch.
+pub fn regenerate_pk<C: Circuit<Fr>>(
+    params: &Params<G1Affine>,
+    vk: VerifyingKey<G1Affine>,
+    circuit: &C,
+) -> Result<ProvingKey<G1Affine>, KeyError> {
+    let empty = circuit.without_witnesses();
+    let (mut expected, mut found) = (vec![], vec![]);
+    keygen_vk(params, &empty)?.write(&mut expected)?;
+    vk.write(&mut found)?;
+    if expected != found {
+        return Err(KeyError::WrongShape);
+    }
+    Ok(keygen_pk(params, vk, &empty)?)
+}
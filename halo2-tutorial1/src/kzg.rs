@@ -0,0 +1,304 @@
+//! Runs the tooling around the KZG prover on example3's circuit, so that
+//! example3 itself stays a plain example.
+
+// The circuit is example3's, shared as a module like the cli binary does.
+#[allow(dead_code)]
+#[path = "example3.rs"]
+mod fibo_xor;
+
+use ff::Field;
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::pairing::group::prime::PrimeCurveAffine;
+use halo2_tutorial::{batch, envelope, keys, prover, srs};
+use pairing::bn256::{Fq, Fq2, Fr as Fp, G2Affine};
+
+use fibo_xor::{get_sequence, FiboCircuit};
+use prover::TranscriptKind;
+
+fn main() {
+    let num = 14;
+    let seq = get_sequence(1, 3, 2, num).unwrap();
+    let circuit = FiboCircuit {
+        a: Some(Fp::from(seq[0])),
+        b: Some(Fp::from(seq[1])),
+        c: Some(Fp::from(seq[2])),
+        num,
+    };
+    let k = 11;
+
+    persistence_test(k, circuit.clone());
+    batch_verify_test(k, num);
+    envelope_test(k, circuit.clone());
+    transcript_test(k, circuit.clone());
+    srs_test(k, circuit);
+}
+
+/// Saves the parameters and verifying key, then proves again from the saved
+/// files alone.
+fn persistence_test(k: u32, circuit: FiboCircuit<Fp>) {
+    let dir = std::env::temp_dir();
+    let params_path = dir.join("kzg.params");
+    let vk_path = dir.join("kzg.vk");
+
+    let (params, params_verifier) = prover::setup(k, 0).unwrap();
+    let pk = prover::keygen(&params, &circuit).unwrap();
+    keys::write_params(&params_path, &params).unwrap();
+    keys::write_vk::<FiboCircuit<Fp>>(&vk_path, k, pk.get_vk()).unwrap();
+
+    // A proof from the reloaded files verifies against the original key.
+    let params = keys::read_params(&params_path, k).unwrap();
+    let vk = keys::read_vk::<FiboCircuit<Fp>>(&vk_path, &params).unwrap();
+    let reloaded = keys::regenerate_pk(&params, vk, &circuit).unwrap();
+    let proof = prover::prove(&params, &reloaded, &circuit, &[], TranscriptKind::Blake2b).unwrap();
+    prover::verify(&params_verifier, pk.get_vk(), &[], &proof, TranscriptKind::Blake2b).unwrap();
+    prover::verify(&params_verifier, reloaded.get_vk(), &[], &proof, TranscriptKind::Blake2b).unwrap();
+
+    // Files for another k or another circuit shape are rejected.
+    let err = keys::read_params(&params_path, k + 1).unwrap_err();
+    assert!(matches!(err, keys::KeyError::WrongK { .. }), "{}", err);
+    println!("params for k = {}: {}", k + 1, err);
+
+    let err = keys::read_vk::<FiboCircuit<Fp>>(&params_path, &params).unwrap_err();
+    assert!(matches!(err, keys::KeyError::NotAKeyFile), "{}", err);
+
+    let longer = FiboCircuit { num: circuit.num + 1, ..circuit };
+    let vk = keys::read_vk::<FiboCircuit<Fp>>(&vk_path, &params).unwrap();
+    let err = keys::regenerate_pk(&params, vk, &longer).unwrap_err();
+    assert!(matches!(err, keys::KeyError::WrongShape), "{}", err);
+    println!("vk for {} rows, circuit of {}: {}", circuit.num, longer.num, err);
+}
+
+/// Proves against parameters loaded from a powers-of-tau file. The file is
+/// a stand-in for a ceremony's output, written from a known tau.
+fn srs_test(k: u32, circuit: FiboCircuit<Fp>) {
+    let path = std::env::temp_dir().join("kzg.ptau");
+    let tau = Fp::from(0x5eed);
+    srs::write_unsafe(&path, k + 1, tau).unwrap();
+
+    // The ceremony covers one more power of two than the circuit needs.
+    let params = srs::load(&path, k).unwrap();
+    let (params, params_verifier) = prover::with_verifier(params, 0).unwrap();
+    let pk = prover::keygen(&params, &circuit).unwrap();
+    let proof = prover::prove(&params, &pk, &circuit, &[], TranscriptKind::Blake2b).unwrap();
+    prover::verify(&params_verifier, pk.get_vk(), &[], &proof, TranscriptKind::Blake2b).unwrap();
+
+    let err = srs::load(&path, k + 2).unwrap_err();
+    assert!(matches!(err, srs::SrsError::Degree { .. }), "{}", err);
+    println!("ptau for k = {}: {}", k + 2, err);
+
+    // A point off the curve is rejected.
+    let other = std::env::temp_dir().join("kzg-other.ptau");
+    let first_g1 = 4 + 4 + 4 + (4 + 8) + (4 + 32 + 4 + 4) + (4 + 8);
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[first_g1 + 64] ^= 1;
+    std::fs::write(&other, &bytes).unwrap();
+    let err = srs::load(&other, k).unwrap_err();
+    assert!(matches!(err, srs::SrsError::Encoding { index: 1, .. }), "{}", err);
+    println!("corrupted point: {}", err);
+
+    // So is a point on the G2 curve outside the prime-order subgroup, in
+    // place of tau in G2.
+    let mut bytes = std::fs::read(&path).unwrap();
+    let s_g2 = bytes.len() - (2usize << k) * 128 + 128;
+    let mut point = vec![];
+    srs::write_g2(&mut point, &off_subgroup_g2());
+    bytes[s_g2..s_g2 + 128].copy_from_slice(&point);
+    std::fs::write(&other, &bytes).unwrap();
+    let err = srs::load(&other, k).unwrap_err();
+    assert!(matches!(err, srs::SrsError::Encoding { section: "tau G2", index: 1 }), "{}", err);
+    println!("point outside the subgroup: {}", err);
+
+    // A G2 section from another tau fails the pairing check.
+    srs::write_unsafe(&other, k + 1, tau + Fp::one()).unwrap();
+    let mut bytes = std::fs::read(&path).unwrap();
+    let other_bytes = std::fs::read(&other).unwrap();
+    let g2_len = (2usize << k) * 128;
+    let start = bytes.len() - g2_len;
+    bytes[start..].copy_from_slice(&other_bytes[start..]);
+    std::fs::write(&other, &bytes).unwrap();
+    let err = srs::load(&other, k).unwrap_err();
+    assert!(matches!(err, srs::SrsError::Pairing), "{}", err);
+    println!("mixed ceremonies: {}", err);
+}
+
+/// A point of the G2 curve `y^2 = x^3 + b` outside the subgroup of order
+/// `r`. The cofactor is about `r`, so almost every point on the curve is.
+fn off_subgroup_g2() -> G2Affine {
+    let generator = G2Affine::generator().coordinates().unwrap();
+    let (x, y) = (*generator.x(), *generator.y());
+    let b = y.square() - x.square() * x;
+    (1..)
+        .find_map(|i| {
+            let x = Fq2 { c0: Fq::from(i), c1: Fq::zero() };
+            let y = Option::<Fq2>::from((x.square() * x + b).sqrt())?;
+            Option::from(G2Affine::from_xy(x, y))
+        })
+        .unwrap()
+}
+
+/// Verifies proofs of sequences with different first terms in one batch,
+/// then finds the corrupted ones among them.
+fn batch_verify_test(k: u32, num: usize) {
+    let (params, params_verifier) = prover::setup(k, 0).unwrap();
+    let circuits: Vec<_> = (0..8)
+        .map(|x| {
+            // Equal first terms keep every xor operand inside the table.
+            let seq = get_sequence(x, x, x, num).unwrap();
+            FiboCircuit {
+                a: Some(Fp::from(seq[0])),
+                b: Some(Fp::from(seq[1])),
+                c: Some(Fp::from(seq[2])),
+                num,
+            }
+        })
+        .collect();
+    let pk = prover::keygen(&params, &circuits[0]).unwrap();
+    let proofs: Vec<_> = circuits
+        .iter()
+        .map(|circuit| prover::prove(&params, &pk, circuit, &[], TranscriptKind::Blake2b).unwrap())
+        .collect();
+
+    let start = std::time::Instant::now();
+    for proof in &proofs {
+        prover::verify(&params_verifier, pk.get_vk(), &[], proof, TranscriptKind::Blake2b).unwrap();
+    }
+    let single = start.elapsed();
+
+    let mut batch = batch::BatchVerifier::new(&params_verifier, pk.get_vk(), TranscriptKind::Blake2b);
+    for proof in &proofs {
+        batch.add(vec![], proof.clone());
+    }
+    let start = std::time::Instant::now();
+    assert_eq!(batch.verify(), Vec::<usize>::new());
+    println!(
+        "{} proofs verified one by one in {:?}, as a batch in {:?}",
+        batch.len(),
+        single,
+        start.elapsed()
+    );
+
+    // One proof with a flipped byte and one cut short are both found.
+    let mut batch = batch::BatchVerifier::new(&params_verifier, pk.get_vk(), TranscriptKind::Blake2b);
+    for (i, proof) in proofs.iter().enumerate() {
+        let (mut proof, len) = (proof.clone(), proof.len());
+        match i {
+            2 => proof[len / 2] ^= 1,
+            5 => proof.truncate(len - 1),
+            _ => {}
+        }
+        batch.add(vec![], proof);
+    }
+    assert_eq!(batch.verify(), vec![2, 5]);
+
+    // Keccak proofs batch the same way. A batch reads every proof with its
+    // own transcript, so a Blake2b proof among them fails.
+    let mut batch = batch::BatchVerifier::new(&params_verifier, pk.get_vk(), TranscriptKind::Keccak256);
+    for circuit in &circuits {
+        batch.add(vec![], prover::prove(&params, &pk, circuit, &[], TranscriptKind::Keccak256).unwrap());
+    }
+    assert_eq!(batch.verify(), Vec::<usize>::new());
+    batch.add(vec![], proofs[0].clone());
+    assert_eq!(batch.verify(), vec![circuits.len()]);
+}
+
+/// Round-trips points, scalars and challenges through the Keccak transcript,
+/// then proves with it.
+fn transcript_test(k: u32, circuit: FiboCircuit<Fp>) {
+    use halo2_proofs::pairing::bn256::G1Affine;
+    use halo2_proofs::pairing::group::{ff::PrimeField, prime::PrimeCurveAffine};
+    use halo2_proofs::transcript::{Challenge255, Transcript, TranscriptRead, TranscriptWrite};
+    use halo2_tutorial::keccak::{keccak256, Keccak256Read, Keccak256Write};
+
+    let hex = |bytes: [u8; 32]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    assert_eq!(hex(keccak256(b"")), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+    assert_eq!(hex(keccak256(b"abc")), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+    // 136 bytes fill the rate exactly, so the padding takes a block of its own.
+    assert_eq!(hex(keccak256(&[0; 136])), "3a5912a7c5faa06ee4fe906253e339467a9ce87d533c65be3c15cb231cdb25f9");
+
+    let points = [G1Affine::generator(), G1Affine::identity()];
+    let scalars = [Fp::zero(), -Fp::one()];
+    let mut writer = Keccak256Write::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    let mut challenges = vec![];
+    for (point, scalar) in points.iter().zip(&scalars) {
+        writer.write_point(*point).unwrap();
+        writer.write_scalar(*scalar).unwrap();
+        challenges.push(*writer.squeeze_challenge_scalar::<()>());
+    }
+    let bytes = writer.finalize();
+    // Points are x and y, scalars one word each, as the EVM reads them.
+    assert_eq!(bytes.len(), 2 * (64 + 32));
+    assert!(bytes[96..160].iter().all(|b| *b == 0));
+    let mut minus_one = (-Fp::one()).to_repr();
+    minus_one.as_mut().reverse();
+    assert_eq!(&bytes[160..192], minus_one.as_ref());
+
+    let mut reader = Keccak256Read::<_, G1Affine, Challenge255<_>>::init(&bytes[..]);
+    for ((point, scalar), challenge) in points.iter().zip(&scalars).zip(&challenges) {
+        assert_eq!(reader.read_point().unwrap(), *point);
+        assert_eq!(reader.read_scalar().unwrap(), *scalar);
+        assert_eq!(*reader.squeeze_challenge_scalar::<()>(), *challenge);
+    }
+
+    // A scalar at or above the modulus is not a valid encoding.
+    let mut reader = Keccak256Read::<_, G1Affine, Challenge255<_>>::init(&[0xff; 32][..]);
+    assert!(reader.read_scalar().is_err());
+
+    // Proofs only verify with the transcript they were made with.
+    let (params, params_verifier) = prover::setup(k, 0).unwrap();
+    let pk = prover::keygen(&params, &circuit).unwrap();
+    let proof = prover::prove(&params, &pk, &circuit, &[], TranscriptKind::Keccak256).unwrap();
+    prover::verify(&params_verifier, pk.get_vk(), &[], &proof, TranscriptKind::Keccak256).unwrap();
+    assert!(prover::verify(&params_verifier, pk.get_vk(), &[], &proof, TranscriptKind::Blake2b).is_err());
+    println!("keccak proof size is {} bytes", proof.len());
+}
+
+/// Wraps a proof in an envelope, reads it back from binary and JSON, and
+/// checks that it is refused against the wrong circuit, `k` or key.
+fn envelope_test(k: u32, circuit: FiboCircuit<Fp>) {
+    use envelope::{Envelope, EnvelopeError};
+
+    let (params, params_verifier) = prover::setup(k, 1).unwrap();
+    let pk = prover::keygen(&params, &circuit).unwrap();
+    let vk = pk.get_vk();
+    let proof = prover::prove(&params, &pk, &circuit, &[], TranscriptKind::Keccak256).unwrap();
+    // The circuit exposes nothing, so carry a column of its own to round-trip.
+    let instances = vec![vec![Fp::from(7), -Fp::one()]];
+    let envelope = Envelope::new("fibo-xor", k, vk, TranscriptKind::Keccak256, instances, proof).unwrap();
+
+    let mut bytes = vec![];
+    envelope.write(&mut bytes).unwrap();
+    assert_eq!(Envelope::read(&mut bytes.as_slice()).unwrap(), envelope);
+    let json = envelope.to_json().unwrap();
+    assert_eq!(Envelope::from_json(json.as_bytes()).unwrap(), envelope);
+    println!("envelope is {} bytes, {} as json", bytes.len(), json.len());
+
+    envelope.check("fibo-xor", k, vk).unwrap();
+    let err = envelope.check("fibo", k, vk).unwrap_err();
+    assert!(matches!(err, EnvelopeError::WrongCircuit { .. }), "{}", err);
+    let err = envelope.check("fibo-xor", k + 1, vk).unwrap_err();
+    assert!(matches!(err, EnvelopeError::WrongK { .. }), "{}", err);
+    let mut other = envelope.clone();
+    other.vk_hash[0] ^= 1;
+    let err = other.check("fibo-xor", k, vk).unwrap_err();
+    assert!(matches!(err, EnvelopeError::WrongVk), "{}", err);
+
+    // The matching envelope is verified with the transcript it names, and
+    // fails only because of the extra public inputs.
+    let err = envelope.verify(&params_verifier, "fibo-xor", k, vk).unwrap_err();
+    assert!(matches!(err, EnvelopeError::Rejected(_)), "{}", err);
+    let envelope = Envelope {
+        instances: vec![],
+        ..envelope
+    };
+    envelope.verify(&params_verifier, "fibo-xor", k, vk).unwrap();
+
+    // Other versions and truncated envelopes are refused.
+    bytes[4] = envelope::VERSION + 1;
+    let err = Envelope::read(&mut bytes.as_slice()).unwrap_err();
+    assert!(matches!(err, EnvelopeError::Version(_)), "{}", err);
+    bytes[4] = envelope::VERSION;
+    bytes.pop();
+    assert!(matches!(Envelope::read(&mut bytes.as_slice()), Err(EnvelopeError::Io(_))));
+    let json = json.replace("\"version\": 1", "\"version\": 2");
+    assert!(matches!(Envelope::from_json(json.as_bytes()), Err(EnvelopeError::Version(2))));
+}
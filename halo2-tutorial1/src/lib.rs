@@ -0,0 +1,19 @@
+//! The chips and tooling the example binaries share. Each binary in `src`
+//! is one example and takes the modules it needs from here, so a binary
+//! that runs several circuits (`cli`, `kzg`) still has one copy of each.
+
+pub mod batch;
+pub mod bits;
+pub mod boolean;
+pub mod convert;
+pub mod envelope;
+pub mod expr;
+pub mod field_chip;
+pub mod keccak;
+pub mod keys;
+pub mod lt;
+pub mod mux;
+pub mod prover;
+pub mod range;
+pub mod srs;
+pub mod table;
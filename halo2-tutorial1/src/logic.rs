@@ -0,0 +1,185 @@
+use halo2_tutorial::{boolean, prover};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+
+use boolean::{BoolOp, BooleanChip, BooleanConfig, BooleanInstructions};
+
+fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> BooleanConfig {
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    BooleanChip::configure(meta, advice, instance)
+}
+
+/// Proves `out = a op b` bitwise, with `out` exposed on instance rows
+/// `0..n`. `forged` replaces the honest `out`.
+struct BitwiseCircuit<F> {
+    op: BoolOp,
+    a: Vec<Option<F>>,
+    b: Vec<Option<F>>,
+    forged: Option<Vec<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for BitwiseCircuit<F> {
+    type Config = BooleanConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            op: self.op,
+            a: vec![None; self.a.len()],
+            b: vec![None; self.b.len()],
+            forged: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = BooleanChip::construct(config);
+
+        let a = chip.assign_bits(layouter.namespace(|| "load a"), &self.a)?;
+        let b = chip.assign_bits(layouter.namespace(|| "load b"), &self.b)?;
+
+        let out = match &self.forged {
+            Some(out) => {
+                let out = out.iter().copied().map(Some).collect();
+                chip.assign_op(layouter.namespace(|| "a op b"), self.op, &a, &b, out)?
+            }
+            None => chip.bitwise(layouter.namespace(|| "a op b"), self.op, &a, &b)?,
+        };
+
+        for (i, bit) in out.iter().enumerate() {
+            chip.expose_public(layouter.namespace(|| format!("expose out {}", i)), bit, i)?;
+        }
+        Ok(())
+    }
+}
+
+/// Proves `(sum, carry)` of a full adder, exposed on instance rows 0 and 1.
+#[derive(Default)]
+struct FullAdderCircuit<F> {
+    a: Option<F>,
+    b: Option<F>,
+    c: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for FullAdderCircuit<F> {
+    type Config = BooleanConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = BooleanChip::construct(config);
+
+        let a = chip.assign_bit(layouter.namespace(|| "load a"), self.a)?;
+        let b = chip.assign_bit(layouter.namespace(|| "load b"), self.b)?;
+        let c = chip.assign_bit(layouter.namespace(|| "load c"), self.c)?;
+
+        let a_xor_b = chip.xor(layouter.namespace(|| "a ^ b"), &a, &b)?;
+        let sum = chip.xor(layouter.namespace(|| "a ^ b ^ c"), &a_xor_b, &c)?;
+
+        // carry = (a & b) | (c & (a ^ b)), with the OR spelled as NAND(NOT x, NOT y).
+        let ab = chip.and(layouter.namespace(|| "a & b"), &a, &b)?;
+        let c_ab = chip.and(layouter.namespace(|| "c & (a ^ b)"), &c, &a_xor_b)?;
+        let not_ab = chip.not(layouter.namespace(|| "!(a & b)"), &ab)?;
+        let not_c_ab = chip.not(layouter.namespace(|| "!(c & (a ^ b))"), &c_ab)?;
+        let carry = chip.nand(layouter.namespace(|| "carry"), &not_ab, &not_c_ab)?;
+        let carry_or = chip.or(layouter.namespace(|| "carry"), &ab, &c_ab)?;
+
+        chip.expose_public(layouter.namespace(|| "expose sum"), &sum, 0)?;
+        chip.expose_public(layouter.namespace(|| "expose carry"), &carry, 1)?;
+        chip.expose_public(layouter.namespace(|| "expose carry"), &carry_or, 1)
+    }
+}
+
+fn bits(s: &str) -> Vec<Fp> {
+    s.chars().map(|c| Fp::from((c == '1') as u64)).collect()
+}
+
+fn witness(s: &str) -> Vec<Option<Fp>> {
+    bits(s).into_iter().map(Some).collect()
+}
+
+fn bitwise_test(k: u32) {
+    let ops = [
+        (BoolOp::And, "1000"),
+        (BoolOp::Or, "1110"),
+        (BoolOp::Xor, "0110"),
+        (BoolOp::Nand, "0111"),
+        (BoolOp::Not, "0011"),
+    ];
+
+    // Each truth table as a 4-bit vector: a = 1100, b = 1010.
+    for (op, expected) in ops {
+        let b = if op == BoolOp::Not { vec![] } else { witness("1010") };
+        let circuit = BitwiseCircuit { op, a: witness("1100"), b, forged: None };
+        let prover = MockProver::run(k, &circuit, vec![bits(expected)]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{:?}", op);
+
+        let prover = MockProver::run(k, &circuit, vec![bits("0000")]).unwrap();
+        assert_eq!(prover.verify().is_err(), expected != "0000", "{:?}", op);
+
+        // A flipped output bit, exposed as claimed.
+        let mut claimed = bits(expected);
+        claimed[0] = Fp::one() - claimed[0];
+        let circuit = BitwiseCircuit { forged: Some(claimed.clone()), ..circuit };
+        let prover = MockProver::run(k, &circuit, vec![claimed]).unwrap();
+        assert!(prover.verify().is_err(), "{:?}", op);
+    }
+
+    let circuit = BitwiseCircuit { op: BoolOp::Xor, a: witness("1100"), b: witness("1010"), forged: None };
+    println!("{}", prover::round_trip(k, &circuit, &[bits("0110")]).unwrap());
+
+    // A non-boolean input, with the output 2 AND 1 = 2 that satisfies the
+    // and gate. Only the bit gate on the inputs rejects it.
+    let circuit = BitwiseCircuit {
+        op: BoolOp::And,
+        a: vec![Some(Fp::from(2))],
+        b: witness("1"),
+        forged: Some(vec![Fp::from(2)]),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(2)]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Mismatched lengths fail synthesis.
+    let circuit = BitwiseCircuit { op: BoolOp::Xor, a: witness("110"), b: witness("10"), forged: None };
+    assert!(MockProver::run(k, &circuit, vec![bits("000")]).is_err());
+}
+
+fn full_adder_test(k: u32) {
+    for n in 0..8u64 {
+        let (a, b, c) = (n & 1, (n >> 1) & 1, (n >> 2) & 1);
+        let total = a + b + c;
+        let circuit = FullAdderCircuit { a: Some(Fp::from(a)), b: Some(Fp::from(b)), c: Some(Fp::from(c)) };
+
+        let public_inputs = vec![Fp::from(total & 1), Fp::from(total >> 1)];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{} + {} + {}", a, b, c);
+
+        let public_inputs = vec![Fp::from(total & 1), Fp::from(1 - (total >> 1))];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err(), "{} + {} + {}", a, b, c);
+    }
+}
+
+fn main() {
+    let k = 5;
+
+    bitwise_test(k);
+    full_adder_test(k);
+}
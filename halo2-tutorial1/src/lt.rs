@@ -0,0 +1,137 @@
+#![allow(dead_code)]
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::expr::{bool_check, expr_from_bytes};
+
+/// Instruction that the Lt chip needs to implement.
+pub trait LtInstruction<F: FieldExt> {
+    /// Assign the lhs and rhs witnesses to the Lt chip's region. Either may be
+    /// unknown, as during key generation.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Option<F>,
+        rhs: Option<F>,
+    ) -> Result<(), Error>;
+}
+/// Config for the Lt chip.
+#[derive(Clone, Copy, Debug)]
+pub struct LtConfig<F, const N_BYTES: usize> {
+    /// Denotes the lt outcome. If lhs < rhs then lt == 1, otherwise lt == 0.
+    pub lt: Column<Advice>,
+    /// Denotes the bytes representation of the difference between lhs and rhs.
+    /// Note that the range of each byte is not checked by this config.
+    pub diff: [Column<Advice>; N_BYTES],
+    /// Denotes the range within which both lhs and rhs lie.
+    pub range: F,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> LtConfig<F, N_BYTES> {
+    /// Returns an expression that denotes whether lhs < rhs, or not.
+    pub fn is_lt(&self, meta: &mut VirtualCells<F>, rotation: Option<Rotation>) -> Expression<F> {
+        meta.query_advice(self.lt, rotation.unwrap_or_else(Rotation::cur))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LtChip<F, const N_BYTES: usize> {
+    config: LtConfig<F, N_BYTES>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> LtChip<F, N_BYTES> {
+    /// Configures the Lt chip.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
+    ) -> LtConfig<F, N_BYTES> {
+        let lt = meta.advice_column();
+        let diff = [(); N_BYTES].map(|_| meta.advice_column());
+        let range = F::from(2).pow(&[(N_BYTES * 8) as u64, 0, 0, 0]);
+
+        meta.create_gate("lt gate", |meta| {
+            let q_enable = q_enable(meta);
+            let lt = meta.query_advice(lt, Rotation::cur());
+
+            let diff_bytes = diff
+                .iter()
+                .map(|c| meta.query_advice(*c, Rotation::cur()))
+                .collect::<Vec<Expression<F>>>();
+
+            let check_a =
+                lhs(meta) - rhs(meta) - expr_from_bytes(&diff_bytes) + (lt.clone() * range);
+
+            let check_b = bool_check(lt);
+
+            [check_a, check_b]
+                .into_iter()
+                .map(move |poly| q_enable.clone() * poly)
+        });
+
+        LtConfig { lt, diff, range }
+    }
+
+    /// Constructs a Lt chip given a config.
+    pub fn construct(config: LtConfig<F, N_BYTES>) -> LtChip<F, N_BYTES> {
+        LtChip { config }
+    }
+}
+impl<F: FieldExt, const N_BYTES: usize> LtInstruction<F> for LtChip<F, N_BYTES> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Option<F>,
+        rhs: Option<F>,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        let lt = lhs.zip(rhs).map(|(lhs, rhs)| lhs < rhs);
+        region.assign_advice(
+            || "lt chip: lt",
+            config.lt,
+            offset,
+            || lt.map(|lt| F::from(lt as u64)).ok_or(Error::Synthesis),
+        )?;
+
+        let diff = lhs.zip(rhs).zip(lt).map(|((lhs, rhs), lt)| {
+            let diff = (lhs - rhs) + (if lt { config.range } else { F::zero() });
+            diff.to_repr()
+        });
+        for (idx, diff_column) in config.diff.iter().enumerate() {
+            region.assign_advice(
+                || format!("lt chip: diff byte {}", idx),
+                *diff_column,
+                offset,
+                || {
+                    diff.as_ref()
+                        .map(|bytes| F::from(bytes.as_ref()[idx] as u64))
+                        .ok_or(Error::Synthesis)
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: FieldExt, const N_BYTES: usize> Chip<F> for LtChip<F, N_BYTES> {
+    type Config = LtConfig<F, N_BYTES>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
@@ -0,0 +1,206 @@
+use halo2_tutorial::{mux, prover};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+
+use mux::{MuxChip, MuxConfig, MuxInstructions};
+
+fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> MuxConfig {
+    let advice = [(); 5].map(|_| meta.advice_column());
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    MuxChip::configure(meta, advice, instance, constant)
+}
+
+/// Proves `out = cond ? a : b` with `out` exposed on instance row 0.
+#[derive(Default)]
+pub(crate) struct SelectCircuit<F> {
+    pub(crate) cond: Option<F>,
+    pub(crate) a: Option<F>,
+    pub(crate) b: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for SelectCircuit<F> {
+    type Config = MuxConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = MuxChip::construct(config);
+
+        let cond = chip.load_private(layouter.namespace(|| "load cond"), self.cond)?;
+        let a = chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+        let out = chip.select(layouter.namespace(|| "cond ? a : b"), &cond, &a, &b)?;
+
+        chip.expose_public(layouter.namespace(|| "expose out"), &out, 0)
+    }
+}
+
+/// Proves `out = sum(sel[i] * values[i])` with `out` exposed on instance row 0.
+struct OneHotCircuit<F> {
+    sel: Vec<Option<F>>,
+    values: Vec<Option<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for OneHotCircuit<F> {
+    type Config = MuxConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            sel: vec![None; self.sel.len()],
+            values: vec![None; self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = MuxChip::construct(config);
+
+        let sel = chip.load_private_array(layouter.namespace(|| "load sel"), &self.sel)?;
+        let values = chip.load_private_array(layouter.namespace(|| "load values"), &self.values)?;
+        let out = chip.select_one_hot(layouter.namespace(|| "select"), &sel, &values)?;
+
+        chip.expose_public(layouter.namespace(|| "expose out"), &out, 0)
+    }
+}
+
+/// Proves `out = arr[idx]` over a private array, with `out` exposed on
+/// instance row 0. `forged_sel` replaces the one-hot vector derived from `idx`.
+struct IndexCircuit<F> {
+    idx: Option<F>,
+    arr: Vec<Option<F>>,
+    forged_sel: Option<Vec<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for IndexCircuit<F> {
+    type Config = MuxConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            idx: None,
+            arr: vec![None; self.arr.len()],
+            forged_sel: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = MuxChip::construct(config);
+
+        let idx = chip.load_private(layouter.namespace(|| "load idx"), self.idx)?;
+        let arr = chip.load_private_array(layouter.namespace(|| "load arr"), &self.arr)?;
+        let out = match &self.forged_sel {
+            None => chip.select_index(layouter.namespace(|| "arr[idx]"), &idx, &arr)?,
+            Some(sel) => {
+                let sel: Vec<_> = sel.iter().copied().map(Some).collect();
+                chip.assign_index(layouter.namespace(|| "arr[idx]"), &idx, &arr, &sel)?
+            }
+        };
+
+        chip.expose_public(layouter.namespace(|| "expose out"), &out, 0)
+    }
+}
+
+fn select_test(k: u32) {
+    let (a, b) = (Fp::from(2), Fp::from(3));
+    for (cond, out) in [(Fp::one(), a), (Fp::zero(), b)] {
+        let circuit = SelectCircuit { cond: Some(cond), a: Some(a), b: Some(b) };
+        let prover = MockProver::run(k, &circuit, vec![vec![out]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![out + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    let circuit = SelectCircuit { cond: Some(Fp::one()), a: Some(a), b: Some(b) };
+    println!("{}", prover::round_trip(k, &circuit, &[vec![a]]).unwrap());
+
+    // The forgery test2.rs accepts: with cond = 2, 2 * 3 + (1 - 2) * 6 = 0.
+    let circuit = SelectCircuit {
+        cond: Some(Fp::from(2)),
+        a: Some(Fp::from(3)),
+        b: Some(Fp::from(6)),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero()]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+fn one_hot_test(k: u32) {
+    let values: Vec<Fp> = [10, 20, 30].iter().map(|v| Fp::from(*v)).collect();
+    let known: Vec<Option<Fp>> = values.iter().copied().map(Some).collect();
+    let sel = |s: [i32; 3]| -> Vec<Option<Fp>> {
+        s.iter()
+            .map(|s| Some(Fp::from(s.unsigned_abs() as u64) * if *s < 0 { -Fp::one() } else { Fp::one() }))
+            .collect()
+    };
+
+    for i in 0..values.len() {
+        let mut s = [0; 3];
+        s[i] = 1;
+        let circuit = OneHotCircuit { sel: sel(s), values: known.clone() };
+        let prover = MockProver::run(k, &circuit, vec![vec![values[i]]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "sel {:?}", s);
+    }
+
+    // Nothing selected, two selected, and a non-boolean vector that still sums to 1.
+    for s in [[0, 0, 0], [1, 1, 0], [2, -1, 0]] {
+        let circuit = OneHotCircuit { sel: sel(s), values: known.clone() };
+        let out = circuit.sel.iter().zip(&values).fold(Fp::zero(), |acc, (s, v)| acc + s.unwrap() * v);
+        let prover = MockProver::run(k, &circuit, vec![vec![out]]).unwrap();
+        assert!(prover.verify().is_err(), "sel {:?}", s);
+    }
+}
+
+fn index_test(k: u32) {
+    let arr: Vec<Fp> = [7, 11, 13, 17].iter().map(|v| Fp::from(*v)).collect();
+    let known: Vec<Option<Fp>> = arr.iter().copied().map(Some).collect();
+
+    for (i, value) in arr.iter().enumerate() {
+        let circuit = IndexCircuit { idx: Some(Fp::from(i as u64)), arr: known.clone(), forged_sel: None };
+        let prover = MockProver::run(k, &circuit, vec![vec![*value]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "arr[{}]", i);
+
+        let prover = MockProver::run(k, &circuit, vec![vec![*value + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err(), "arr[{}]", i);
+    }
+
+    // Out of bounds.
+    let circuit = IndexCircuit { idx: Some(Fp::from(4)), arr: known.clone(), forged_sel: None };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero()]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // A one-hot vector that points at arr[2] while idx = 1.
+    let forged_sel = vec![Fp::zero(), Fp::zero(), Fp::one(), Fp::zero()];
+    let circuit = IndexCircuit { idx: Some(Fp::one()), arr: known, forged_sel: Some(forged_sel) };
+    let prover = MockProver::run(k, &circuit, vec![vec![arr[2]]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+fn main() {
+    let k = 5;
+
+    select_test(k);
+    one_hot_test(k);
+    index_test(k);
+}
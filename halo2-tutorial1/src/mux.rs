@@ -0,0 +1,360 @@
+#![allow(dead_code)]
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::expr::{bool_check, select};
+
+pub trait MuxInstructions<F: FieldExt>: Chip<F> {
+    /// Loads a number into the circuit as a private input.
+    fn load_private(&self, layouter: impl Layouter<F>, value: Option<F>) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Loads an array into the circuit as private inputs.
+    fn load_private_array(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Option<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>;
+
+    /// Constrains `x` to be 0 or 1.
+    fn assert_bool(&self, layouter: impl Layouter<F>, x: &AssignedCell<F, F>) -> Result<(), Error>;
+
+    /// Returns `cond ? a : b`. The circuit is unsatisfiable unless `cond` is 0 or 1.
+    fn select(
+        &self,
+        layouter: impl Layouter<F>,
+        cond: &AssignedCell<F, F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Returns `sum(sel[i] * values[i])`. The circuit is unsatisfiable unless
+    /// exactly one `sel[i]` is 1 and the rest are 0.
+    fn select_one_hot(
+        &self,
+        layouter: impl Layouter<F>,
+        sel: &[AssignedCell<F, F>],
+        values: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Returns `values[idx]`. The circuit is unsatisfiable if `idx` is out of
+    /// bounds.
+    fn select_index(
+        &self,
+        layouter: impl Layouter<F>,
+        idx: &AssignedCell<F, F>,
+        values: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct MuxConfig {
+    pub advice: [Column<Advice>; 5],
+    pub instance: Column<Instance>,
+    /// Row positions `0, 1, ..., n - 1` of an index selection.
+    pos: Column<Fixed>,
+    s_bool: Selector,
+    s_select: Selector,
+    s_one_hot: Selector,
+    s_index: Selector,
+}
+
+pub struct MuxChip<F: FieldExt> {
+    config: MuxConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for MuxChip<F> {
+    type Config = MuxConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> MuxChip<F> {
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> <Self as Chip<F>>::Config {
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for col in &advice {
+            meta.enable_equality(*col);
+        }
+
+        let config = MuxConfig {
+            advice,
+            instance,
+            pos: meta.fixed_column(),
+            s_bool: meta.selector(),
+            s_select: meta.selector(),
+            s_one_hot: meta.selector(),
+            s_index: meta.selector(),
+        };
+
+        meta.create_gate("bool", |meta| {
+            let s = meta.query_selector(config.s_bool);
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            vec![s * bool_check(x)]
+        });
+
+        meta.create_gate("select", |meta| {
+            // | a0   | a1 | a2 | a3  | s_select |
+            // |------|----|----|-----|----------|
+            // | cond | a  | b  | out |    1     |
+            let s = meta.query_selector(config.s_select);
+            let cond = meta.query_advice(advice[0], Rotation::cur());
+            let a = meta.query_advice(advice[1], Rotation::cur());
+            let b = meta.query_advice(advice[2], Rotation::cur());
+            let out = meta.query_advice(advice[3], Rotation::cur());
+            vec![
+                s.clone() * bool_check(cond.clone()),
+                s * (select(cond, a, b) - out),
+            ]
+        });
+
+        meta.create_gate("one hot", |meta| {
+            // | a0  | a1  | a2  | a3  | s_one_hot |
+            // |-----|-----|-----|-----|-----------|
+            // | s_0 | v_0 |  0  |  0  |     1     |
+            // | s_1 | v_1 | acc | sum |     1     |
+            // | ... | ... | ... | ... |    ...    |
+            // |     |     | out |  1  |           |
+            //
+            // `acc` and `sum` start at constant 0, and the final `sum` is
+            // constrained to constant 1 by the region.
+            let s = meta.query_selector(config.s_one_hot);
+            let sel = meta.query_advice(advice[0], Rotation::cur());
+            let v = meta.query_advice(advice[1], Rotation::cur());
+            let acc = meta.query_advice(advice[2], Rotation::cur());
+            let acc_next = meta.query_advice(advice[2], Rotation::next());
+            let sum = meta.query_advice(advice[3], Rotation::cur());
+            let sum_next = meta.query_advice(advice[3], Rotation::next());
+            vec![
+                s.clone() * bool_check(sel.clone()),
+                s.clone() * (acc + sel.clone() * v - acc_next),
+                s * (sum + sel - sum_next),
+            ]
+        });
+
+        meta.create_gate("index", |meta| {
+            // Runs alongside `one hot`, with `idx` copied into a4 on every row.
+            // Only the row whose position equals `idx` may be selected.
+            let s = meta.query_selector(config.s_index);
+            let sel = meta.query_advice(advice[0], Rotation::cur());
+            let idx = meta.query_advice(advice[4], Rotation::cur());
+            let pos = meta.query_fixed(config.pos, Rotation::cur());
+            vec![s * sel * (idx - pos)]
+        });
+
+        config
+    }
+
+    /// Assigns a one-hot selection over `values`, with `assign_sel` placing
+    /// `sel[i]` on row `i`, and returns the selected value.
+    fn assign_one_hot(
+        &self,
+        region: &mut Region<'_, F>,
+        values: &[AssignedCell<F, F>],
+        assign_sel: impl Fn(&mut Region<'_, F>, usize) -> Result<AssignedCell<F, F>, Error>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        if values.is_empty() {
+            return Err(Error::Synthesis);
+        }
+
+        let mut acc = region.assign_advice_from_constant(|| "acc", config.advice[2], 0, F::zero())?;
+        let mut sum = region.assign_advice_from_constant(|| "sum", config.advice[3], 0, F::zero())?;
+
+        for (i, value) in values.iter().enumerate() {
+            config.s_one_hot.enable(region, i)?;
+            let sel = assign_sel(region, i)?;
+            value.copy_advice(|| format!("v_{}", i), region, config.advice[1], i)?;
+
+            let next_acc = acc
+                .value()
+                .zip(sel.value())
+                .zip(value.value())
+                .map(|((acc, sel), v)| *acc + *sel * v);
+            let next_sum = sum.value().zip(sel.value()).map(|(sum, sel)| *sum + sel);
+
+            acc = region.assign_advice(|| "acc", config.advice[2], i + 1, || next_acc.ok_or(Error::Synthesis))?;
+            sum = region.assign_advice(|| "sum", config.advice[3], i + 1, || next_sum.ok_or(Error::Synthesis))?;
+        }
+
+        region.constrain_constant(sum.cell(), F::one())?;
+        Ok(acc)
+    }
+
+    /// Assigns an index selection region with `sel` as the claimed one-hot
+    /// vector. `select_index` derives `sel` from `idx`; tests use this
+    /// directly to check that a forged selector is rejected.
+    pub fn assign_index(
+        &self,
+        mut layouter: impl Layouter<F>,
+        idx: &AssignedCell<F, F>,
+        values: &[AssignedCell<F, F>],
+        sel: &[Option<F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        if sel.len() != values.len() {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "select index",
+            |mut region: Region<'_, F>| {
+                for i in 0..values.len() {
+                    config.s_index.enable(&mut region, i)?;
+                    idx.copy_advice(|| "idx", &mut region, config.advice[4], i)?;
+                    region.assign_fixed(|| "pos", config.pos, i, || Ok(F::from(i as u64)))?;
+                }
+
+                self.assign_one_hot(&mut region, values, |region, i| {
+                    region.assign_advice(|| format!("s_{}", i), config.advice[0], i, || sel[i].ok_or(Error::Synthesis))
+                })
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> MuxInstructions<F> for MuxChip<F> {
+    fn load_private(&self, mut layouter: impl Layouter<F>, value: Option<F>) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "load private",
+            |mut region| region.assign_advice(|| "private input", config.advice[0], 0, || value.ok_or(Error::Synthesis)),
+        )
+    }
+
+    fn load_private_array(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Option<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "load private array",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        region.assign_advice(|| format!("arr[{}]", i), config.advice[1], i, || value.ok_or(Error::Synthesis))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn assert_bool(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>) -> Result<(), Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "assert bool",
+            |mut region| {
+                config.s_bool.enable(&mut region, 0)?;
+                x.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+                Ok(())
+            },
+        )
+    }
+
+    fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cond: &AssignedCell<F, F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                config.s_select.enable(&mut region, 0)?;
+
+                let cond = cond.copy_advice(|| "cond", &mut region, config.advice[0], 0)?;
+                let a = a.copy_advice(|| "a", &mut region, config.advice[1], 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.advice[2], 0)?;
+
+                let out = cond
+                    .value()
+                    .zip(a.value())
+                    .zip(b.value())
+                    .map(|((c, a), b)| *c * a + (F::one() - c) * b);
+                region.assign_advice(|| "out", config.advice[3], 0, || out.ok_or(Error::Synthesis))
+            },
+        )
+    }
+
+    fn select_one_hot(
+        &self,
+        mut layouter: impl Layouter<F>,
+        sel: &[AssignedCell<F, F>],
+        values: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        if sel.len() != values.len() {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "select one hot",
+            |mut region: Region<'_, F>| {
+                self.assign_one_hot(&mut region, values, |region, i| {
+                    sel[i].copy_advice(|| format!("s_{}", i), region, config.advice[0], i)
+                })
+            },
+        )
+    }
+
+    fn select_index(
+        &self,
+        layouter: impl Layouter<F>,
+        idx: &AssignedCell<F, F>,
+        values: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        // An out-of-bounds `idx` selects nothing, which fails the `sum == 1` check.
+        let sel: Vec<Option<F>> = (0..values.len())
+            .map(|i| idx.value().map(|idx| if *idx == F::from(i as u64) { F::one() } else { F::zero() }))
+            .collect();
+        self.assign_index(layouter, idx, values, &sel)
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
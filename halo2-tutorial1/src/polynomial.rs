@@ -0,0 +1,295 @@
+use halo2_tutorial::{field_chip, prover};
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+
+use field_chip::{FieldChip, FieldConfig, NumericInstruction, Number};
+
+/// The coefficients `c_0, c_1, ..., c_n` of `p(x) = c_0 + c_1 x + ... + c_n x^n`,
+/// together with where they are placed in the circuit.
+#[derive(Clone, Debug)]
+enum Coefficients<F> {
+    /// Constants baked into the circuit's fixed column.
+    Fixed(Vec<F>),
+    /// Private witnesses, unknown during key generation.
+    Advice(Vec<Option<F>>),
+    /// Rows of the instance column holding each coefficient.
+    Instance(Vec<usize>),
+}
+
+impl<F> Coefficients<F> {
+    fn len(&self) -> usize {
+        match self {
+            Coefficients::Fixed(c) => c.len(),
+            Coefficients::Advice(c) => c.len(),
+            Coefficients::Instance(rows) => rows.len(),
+        }
+    }
+}
+
+trait PolyInstruction<F: FieldExt>: Chip<F> {
+    /// Returns `y = p(x)`.
+    fn evaluate(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &Number<F>,
+        coeffs: &Coefficients<F>,
+    ) -> Result<Number<F>, Error>;
+}
+
+#[derive(Clone, Debug)]
+struct PolyConfig {
+    x: Column<Advice>,
+    acc: Column<Advice>,
+    coeff: Column<Advice>,
+    instance: Column<Instance>,
+    s_horner: Selector,
+}
+
+struct PolyChip<F: FieldExt> {
+    config: PolyConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for PolyChip<F> {
+    type Config = PolyConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> PolyChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> <Self as Chip<F>>::Config {
+        let [x, acc, coeff] = advice;
+        let s_horner = meta.selector();
+
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for col in &advice {
+            meta.enable_equality(*col);
+        }
+
+        meta.create_gate("horner", |meta| {
+            // One Horner step per row, starting from acc = 0:
+            //
+            // |  x  |  acc  | coeff | s_horner |
+            // |-----|-------|-------|----------|
+            // |  x  |  acc  |       |    1     |
+            // |  x  |  acc' |   c   |          |
+            //
+            // acc' = acc * x + c, and x is carried down unchanged.
+            let s = meta.query_selector(s_horner);
+            let x_cur = meta.query_advice(x, Rotation::cur());
+            let x_next = meta.query_advice(x, Rotation::next());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let c = meta.query_advice(coeff, Rotation::next());
+            vec![
+                s.clone() * (acc_cur * x_cur.clone() + c - acc_next),
+                s * (x_next - x_cur),
+            ]
+        });
+
+        PolyConfig {
+            x,
+            acc,
+            coeff,
+            instance,
+            s_horner,
+        }
+    }
+}
+
+impl<F: FieldExt> PolyInstruction<F> for PolyChip<F> {
+    fn evaluate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &Number<F>,
+        coeffs: &Coefficients<F>,
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+        let degree = coeffs.len().checked_sub(1).ok_or(Error::Synthesis)?;
+
+        layouter.assign_region(
+            || "horner",
+            |mut region| {
+                x.0.copy_advice(|| "x", &mut region, config.x, 0)?;
+                let mut acc = region.assign_advice_from_constant(|| "acc", config.acc, 0, F::zero())?;
+
+                // Walk the coefficients from the leading one down to c_0.
+                for step in 0..=degree {
+                    let i = degree - step;
+                    let row = step + 1;
+                    config.s_horner.enable(&mut region, step)?;
+
+                    region.assign_advice(
+                        || "x",
+                        config.x,
+                        row,
+                        || x.0.value().copied().ok_or(Error::Synthesis),
+                    )?;
+
+                    let c = match coeffs {
+                        Coefficients::Fixed(c) => region.assign_advice_from_constant(
+                            || format!("c_{}", i),
+                            config.coeff,
+                            row,
+                            c[i],
+                        )?,
+                        Coefficients::Advice(c) => region.assign_advice(
+                            || format!("c_{}", i),
+                            config.coeff,
+                            row,
+                            || c[i].ok_or(Error::Synthesis),
+                        )?,
+                        Coefficients::Instance(rows) => region.assign_advice_from_instance(
+                            || format!("c_{}", i),
+                            config.instance,
+                            rows[i],
+                            config.coeff,
+                            row,
+                        )?,
+                    };
+
+                    let value = acc
+                        .value()
+                        .zip(x.0.value())
+                        .zip(c.value())
+                        .map(|((acc, x), c)| *acc * *x + *c);
+                    acc = region.assign_advice(
+                        || "acc * x + c",
+                        config.acc,
+                        row,
+                        || value.ok_or(Error::Synthesis),
+                    )?;
+                }
+
+                Ok(Number(acc))
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PolyCircuitConfig {
+    field: FieldConfig,
+    poly: PolyConfig,
+}
+
+/// Proves `y = p(x)` with `y` exposed on instance row 0.
+struct PolyCircuit<F: FieldExt> {
+    x: Option<F>,
+    coeffs: Coefficients<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for PolyCircuit<F> {
+    type Config = PolyCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        let coeffs = match &self.coeffs {
+            Coefficients::Advice(c) => Coefficients::Advice(vec![None; c.len()]),
+            coeffs => coeffs.clone(),
+        };
+        Self {
+            x: None,
+            coeffs,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        PolyCircuitConfig {
+            field: FieldChip::configure(meta, [advice[0], advice[1]], instance, constant),
+            poly: PolyChip::configure(meta, advice, instance, constant),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let field_chip = FieldChip::construct(config.field);
+        let poly_chip = PolyChip::construct(config.poly);
+
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        let y = poly_chip.evaluate(layouter.namespace(|| "p(x)"), &x, &self.coeffs)?;
+
+        field_chip.expose_public(layouter.namespace(|| "expose y"), y, 0)
+    }
+}
+
+/// Native Horner evaluation of `p(x)`, lowest coefficient first.
+fn eval_poly<F: FieldExt>(coeffs: &[F], x: F) -> F {
+    coeffs.iter().rev().fold(F::zero(), |acc, c| acc * x + c)
+}
+
+fn main() {
+    let k = 5;
+
+    // p(x) = 5 - 3x + 2x^3
+    let coeffs = vec![Fp::from(5), -Fp::from(3), Fp::zero(), Fp::from(2)];
+    let x = Fp::from(7);
+    let y = eval_poly(&coeffs, x);
+    assert_eq!(y, Fp::from(5 + 2 * 343) - Fp::from(3 * 7));
+
+    // Coefficients are placed in the instance column after y.
+    let rows: Vec<usize> = (1..=coeffs.len()).collect();
+    let mut instance_inputs = vec![y];
+    instance_inputs.extend(coeffs.iter().copied());
+
+    let cases = vec![
+        (Coefficients::Fixed(coeffs.clone()), vec![y]),
+        (Coefficients::Advice(coeffs.iter().copied().map(Some).collect()), vec![y]),
+        (Coefficients::Instance(rows), instance_inputs),
+    ];
+
+    for (coeffs, public_inputs) in cases {
+        let circuit = PolyCircuit { x: Some(x), coeffs };
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{:?}", circuit.coeffs);
+
+        // The wrong y is rejected.
+        let mut bad_inputs = public_inputs.clone();
+        bad_inputs[0] += Fp::one();
+        let prover = MockProver::run(k, &circuit, vec![bad_inputs]).unwrap();
+        assert!(prover.verify().is_err(), "{:?}", circuit.coeffs);
+    }
+
+    let circuit = PolyCircuit { x: Some(x), coeffs: Coefficients::Fixed(coeffs.clone()) };
+    println!("{}", prover::round_trip(k, &circuit, &[vec![y]]).unwrap());
+
+    // A constant polynomial takes a single Horner step.
+    let circuit = PolyCircuit {
+        x: Some(x),
+        coeffs: Coefficients::Fixed(vec![Fp::from(42)]),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(42)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
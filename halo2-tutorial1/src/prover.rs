@@ -0,0 +1,222 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    pairing::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, SingleVerifier, VerifyingKey},
+    poly::commitment::{Params, ParamsVerifier},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+use crate::keccak::{Keccak256Read, Keccak256Write};
+
+/// The hash used for Fiat-Shamir.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptKind {
+    Blake2b,
+    /// Keccak-256 with EVM encoding, for proofs checked by a Solidity verifier.
+    Keccak256,
+}
+
+impl TranscriptKind {
+    /// The name the `cli` binary and proof envelopes use.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TranscriptKind::Blake2b => "blake2b",
+            TranscriptKind::Keccak256 => "keccak256",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blake2b" => Some(TranscriptKind::Blake2b),
+            "keccak256" => Some(TranscriptKind::Keccak256),
+            _ => None,
+        }
+    }
+}
+
+impl Default for TranscriptKind {
+    fn default() -> Self {
+        TranscriptKind::Blake2b
+    }
+}
+
+/// A proof that verified, with the time spent on each step.
+#[derive(Clone, Debug)]
+pub struct ProofReport {
+    pub proof: Vec<u8>,
+    pub keygen: Duration,
+    pub prove: Duration,
+    pub verify: Duration,
+}
+
+impl ProofReport {
+    /// The proof size in bytes.
+    pub fn size(&self) -> usize {
+        self.proof.len()
+    }
+}
+
+impl fmt::Display for ProofReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "proof size is {} bytes (keygen {:?}, prove {:?}, verify {:?})",
+            self.size(),
+            self.keygen,
+            self.prove,
+            self.verify
+        )
+    }
+}
+
+/// Generates insecure parameters for circuits of `2^k` rows, with a verifier
+/// that accepts instance columns of up to `public_inputs_size` rows. Real
+/// deployments load a ceremony's output with `srs::load` instead.
+pub fn setup(k: u32, public_inputs_size: usize) -> Result<(Params<G1Affine>, ParamsVerifier<Bn256>), Error> {
+    with_verifier(Params::<G1Affine>::unsafe_setup::<Bn256>(k), public_inputs_size)
+}
+
+/// Pairs `params` with a verifier that accepts instance columns of up to
+/// `public_inputs_size` rows.
+pub fn with_verifier(
+    params: Params<G1Affine>,
+    public_inputs_size: usize,
+) -> Result<(Params<G1Affine>, ParamsVerifier<Bn256>), Error> {
+    let params_verifier = params.verifier(public_inputs_size).map_err(Error::Transcript)?;
+    Ok((params, params_verifier))
+}
+
+/// Generates the proving key from `circuit.without_witnesses()`, so keygen
+/// never sees a witness value.
+pub fn keygen<C: Circuit<Fr>>(params: &Params<G1Affine>, circuit: &C) -> Result<ProvingKey<G1Affine>, Error> {
+    let empty = circuit.without_witnesses();
+    let vk = keygen_vk(params, &empty)?;
+    keygen_pk(params, vk, &empty)
+}
+
+/// Proves `circuit` against `instances`, one vector per instance column, and
+/// returns the proof bytes. The verifier must use the same `transcript`.
+pub fn prove<C: Circuit<Fr>>(
+    params: &Params<G1Affine>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: &C,
+    instances: &[Vec<Fr>],
+    transcript: TranscriptKind,
+) -> Result<Vec<u8>, Error> {
+    prove_batch(params, pk, std::slice::from_ref(circuit), &[instances.to_vec()], transcript)
+}
+
+/// Proves all of `circuits` in one proof, `circuits[i]` against
+/// `instances[i]`. The circuits must share the shape `pk` was generated for.
+pub fn prove_batch<C: Circuit<Fr>>(
+    params: &Params<G1Affine>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: &[C],
+    instances: &[Vec<Vec<Fr>>],
+    transcript: TranscriptKind,
+) -> Result<Vec<u8>, Error> {
+    check_batch(circuits, instances)?;
+    let columns: Vec<Vec<&[Fr]>> = instances.iter().map(|circuit| columns(circuit)).collect();
+    let instances: Vec<&[&[Fr]]> = columns.iter().map(|circuit| circuit.as_slice()).collect();
+    match transcript {
+        TranscriptKind::Blake2b => {
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof(params, pk, circuits, &instances, OsRng, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+        TranscriptKind::Keccak256 => {
+            let mut transcript = Keccak256Write::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof(params, pk, circuits, &instances, OsRng, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+    }
+}
+
+/// Verifies `proof` against `instances`, one vector per instance column.
+pub fn verify(
+    params_verifier: &ParamsVerifier<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[Vec<Fr>],
+    proof: &[u8],
+    transcript: TranscriptKind,
+) -> Result<(), Error> {
+    verify_batch(params_verifier, vk, &[instances.to_vec()], proof, transcript)
+}
+
+/// Verifies a proof from `prove_batch` against the instances of every
+/// circuit in it.
+pub fn verify_batch(
+    params_verifier: &ParamsVerifier<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[Vec<Vec<Fr>>],
+    proof: &[u8],
+    transcript: TranscriptKind,
+) -> Result<(), Error> {
+    let columns: Vec<Vec<&[Fr]>> = instances.iter().map(|circuit| columns(circuit)).collect();
+    let instances: Vec<&[&[Fr]]> = columns.iter().map(|circuit| circuit.as_slice()).collect();
+    let strategy = SingleVerifier::new(params_verifier);
+    match transcript {
+        TranscriptKind::Blake2b => {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+            verify_proof(params_verifier, vk, strategy, &instances, &mut transcript)
+        }
+        TranscriptKind::Keccak256 => {
+            let mut transcript = Keccak256Read::<_, _, Challenge255<_>>::init(proof);
+            verify_proof(params_verifier, vk, strategy, &instances, &mut transcript)
+        }
+    }
+}
+
+/// A batch needs at least one circuit, and public inputs for each.
+fn check_batch<C>(circuits: &[C], instances: &[Vec<Vec<Fr>>]) -> Result<(), Error> {
+    if circuits.is_empty() || circuits.len() != instances.len() {
+        return Err(Error::Synthesis);
+    }
+    Ok(())
+}
+
+fn columns(instances: &[Vec<Fr>]) -> Vec<&[Fr]> {
+    instances.iter().map(|column| column.as_slice()).collect()
+}
+
+/// Runs the whole KZG flow for `circuit` on `2^k` rows: setup, keygen,
+/// prove and verify.
+pub fn round_trip<C: Circuit<Fr>>(k: u32, circuit: &C, instances: &[Vec<Fr>]) -> Result<ProofReport, Error> {
+    round_trip_batch(k, std::slice::from_ref(circuit), &[instances.to_vec()], TranscriptKind::default())
+}
+
+/// Runs the whole KZG flow for `circuits`, all proven in one proof.
+pub fn round_trip_batch<C: Circuit<Fr>>(
+    k: u32,
+    circuits: &[C],
+    instances: &[Vec<Vec<Fr>>],
+    transcript: TranscriptKind,
+) -> Result<ProofReport, Error> {
+    check_batch(circuits, instances)?;
+    let public_inputs_size = instances.iter().flatten().map(|column| column.len()).max().unwrap_or(0);
+    let (params, params_verifier) = setup(k, public_inputs_size)?;
+
+    let start = Instant::now();
+    let pk = keygen(&params, &circuits[0])?;
+    let keygen = start.elapsed();
+
+    let start = Instant::now();
+    let proof = prove_batch(&params, &pk, circuits, instances, transcript)?;
+    let prove = start.elapsed();
+
+    let start = Instant::now();
+    verify_batch(&params_verifier, pk.get_vk(), instances, &proof, transcript)?;
+    let verify = start.elapsed();
+
+    Ok(ProofReport {
+        proof,
+        keygen,
+        prove,
+        verify,
+    })
+}
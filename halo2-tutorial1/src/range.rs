@@ -0,0 +1,184 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::expr::range_check;
+use crate::table::RangTableConfig;
+
+/// The degree of a selector-gated lookup of one advice cell into one table
+/// column: 2 for the argument itself, plus 2 for `s * value` and 1 for the
+/// table.
+pub const LOOKUP_DEGREE: usize = 5;
+
+/// How `RangeCheckChip` constrains `0 <= value < range`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeStrategy {
+    /// A single gate `s * value * (1 - value) * ... * (range - 1 - value)`.
+    Polynomial { degree: usize },
+    /// A lookup into a table holding `0..range`.
+    Lookup { table_size: usize },
+}
+
+impl RangeStrategy {
+    /// Picks the polynomial gate if its degree, selector included, fits in
+    /// `max_degree`, and a lookup otherwise. Below `LOOKUP_DEGREE` the lookup
+    /// exceeds the budget as well, but stays far below a large gate.
+    pub fn choose(range: usize, max_degree: usize) -> Self {
+        let degree = range + 1;
+        if degree <= max_degree {
+            RangeStrategy::Polynomial { degree }
+        } else {
+            RangeStrategy::Lookup { table_size: range }
+        }
+    }
+
+    /// The degree this strategy adds to the constraint system.
+    pub fn degree(&self) -> usize {
+        match self {
+            RangeStrategy::Polynomial { degree } => *degree,
+            RangeStrategy::Lookup { .. } => LOOKUP_DEGREE,
+        }
+    }
+}
+
+impl fmt::Display for RangeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeStrategy::Polynomial { degree } => write!(f, "polynomial gate of degree {}", degree),
+            RangeStrategy::Lookup { table_size } => {
+                write!(f, "lookup of degree {} into a table of {} rows", LOOKUP_DEGREE, table_size)
+            }
+        }
+    }
+}
+
+pub trait RangeCheckInstructions<F: FieldExt>: Chip<F> {
+    /// Loads the lookup table, if the chip uses one.
+    fn load(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Loads a private value and constrains `0 <= value < range`.
+    fn assign(&self, layouter: impl Layouter<F>, value: Option<F>) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Constrains `0 <= cell < range`.
+    fn check(&self, layouter: impl Layouter<F>, cell: &AssignedCell<F, F>) -> Result<(), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeCheckConfig<F: FieldExt> {
+    pub value: Column<Advice>,
+    pub range: usize,
+    /// The strategy picked at configure time.
+    pub strategy: RangeStrategy,
+    s_range: Selector,
+    table: Option<RangTableConfig<F>>,
+}
+
+pub struct RangeCheckChip<F: FieldExt> {
+    config: RangeCheckConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for RangeCheckChip<F> {
+    type Config = RangeCheckConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> RangeCheckChip<F> {
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configures `0 <= value < range` within a gate degree budget of
+    /// `max_degree`. A lookup needs `range` rows of table, so the circuit's
+    /// `k` must leave room for them.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        range: usize,
+        max_degree: usize,
+    ) -> <Self as Chip<F>>::Config {
+        meta.enable_equality(value);
+        let strategy = RangeStrategy::choose(range, max_degree);
+
+        let (s_range, table) = match strategy {
+            RangeStrategy::Polynomial { .. } => {
+                let s_range = meta.selector();
+                meta.create_gate("range check", |meta| {
+                    let s = meta.query_selector(s_range);
+                    let value = meta.query_advice(value, Rotation::cur());
+                    vec![s * range_check(value, range)]
+                });
+                (s_range, None)
+            }
+            RangeStrategy::Lookup { .. } => {
+                let s_range = meta.complex_selector();
+                let table = RangTableConfig::configure(meta);
+                meta.lookup("range check", |meta| {
+                    let s = meta.query_selector(s_range);
+                    let value = meta.query_advice(value, Rotation::cur());
+                    vec![(s * value, table.col_value)]
+                });
+                (s_range, Some(table))
+            }
+        };
+
+        RangeCheckConfig {
+            value,
+            range,
+            strategy,
+            s_range,
+            table,
+        }
+    }
+}
+
+impl<F: FieldExt> RangeCheckInstructions<F> for RangeCheckChip<F> {
+    fn load(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        match &self.config.table {
+            Some(table) => table.load(&mut layouter, (0..self.config.range).collect()),
+            None => Ok(()),
+        }
+    }
+
+    fn assign(&self, mut layouter: impl Layouter<F>, value: Option<F>) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                config.s_range.enable(&mut region, 0)?;
+                region.assign_advice(|| "value", config.value, 0, || value.ok_or(Error::Synthesis))
+            },
+        )
+    }
+
+    fn check(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<F, F>) -> Result<(), Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                config.s_range.enable(&mut region, 0)?;
+                cell.copy_advice(|| "value", &mut region, config.value, 0)?;
+                Ok(())
+            },
+        )
+    }
+}
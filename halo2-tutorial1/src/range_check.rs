@@ -0,0 +1,103 @@
+use halo2_tutorial::{prover, range};
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+
+use range::{RangeCheckChip, RangeCheckConfig, RangeCheckInstructions, RangeStrategy};
+
+/// Checks `0 <= v < RANGE` for every value, within a gate degree budget of
+/// `MAX_DEGREE`. The first value is checked a second time through a copy.
+struct RangeCircuit<F, const RANGE: usize, const MAX_DEGREE: usize> {
+    values: Vec<Option<F>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const RANGE: usize, const MAX_DEGREE: usize> RangeCircuit<F, RANGE, MAX_DEGREE> {
+    fn new(values: Vec<Option<F>>) -> Self {
+        Self {
+            values,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, const RANGE: usize, const MAX_DEGREE: usize> Circuit<F> for RangeCircuit<F, RANGE, MAX_DEGREE> {
+    type Config = RangeCheckConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(vec![None; self.values.len()])
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckChip::configure(meta, value, RANGE, MAX_DEGREE)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = RangeCheckChip::construct(config);
+        chip.load(layouter.namespace(|| "range table"))?;
+
+        let mut cells = vec![];
+        for (i, value) in self.values.iter().enumerate() {
+            cells.push(chip.assign(layouter.namespace(|| format!("value {}", i)), *value)?);
+        }
+        if let Some(first) = cells.first() {
+            chip.check(layouter.namespace(|| "copy of value 0"), first)?;
+        }
+        Ok(())
+    }
+}
+
+/// Configures `RangeCircuit` on its own, returning the picked strategy and
+/// the resulting constraint system degree.
+fn configure<const RANGE: usize, const MAX_DEGREE: usize>() -> (RangeStrategy, usize) {
+    let mut meta = ConstraintSystem::<Fp>::default();
+    let config = RangeCircuit::<Fp, RANGE, MAX_DEGREE>::configure(&mut meta);
+    println!("range {} with degree budget {}: {}", RANGE, MAX_DEGREE, config.strategy);
+    (config.strategy, meta.degree())
+}
+
+fn run<const RANGE: usize, const MAX_DEGREE: usize>(k: u32) {
+    let ok = vec![Some(Fp::zero()), Some(Fp::from(RANGE as u64 / 2)), Some(Fp::from(RANGE as u64 - 1))];
+    let circuit = RangeCircuit::<Fp, RANGE, MAX_DEGREE>::new(ok);
+    let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()), "range {}", RANGE);
+    println!("{}", prover::round_trip(k, &circuit, &[]).unwrap());
+
+    for bad in [Fp::from(RANGE as u64), -Fp::one()] {
+        let circuit = RangeCircuit::<Fp, RANGE, MAX_DEGREE>::new(vec![Some(bad), Some(Fp::zero())]);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err(), "range {}", RANGE);
+    }
+}
+
+fn main() {
+    assert_eq!(RangeStrategy::choose(4, 5), RangeStrategy::Polynomial { degree: 5 });
+    assert_eq!(RangeStrategy::choose(4, 4), RangeStrategy::Lookup { table_size: 4 });
+
+    // A small range fits the budget as a gate.
+    let (strategy, degree) = configure::<4, 5>();
+    assert_eq!(strategy, RangeStrategy::Polynomial { degree: 5 });
+    assert_eq!(degree, strategy.degree());
+    run::<4, 5>(4);
+
+    // A slightly larger range falls back to a lookup of the same degree.
+    let (strategy, degree) = configure::<8, 5>();
+    assert_eq!(strategy, RangeStrategy::Lookup { table_size: 8 });
+    assert_eq!(degree, strategy.degree());
+    run::<8, 5>(5);
+
+    // A byte range would be a degree-257 gate.
+    let (strategy, degree) = configure::<256, 5>();
+    assert_eq!(strategy, RangeStrategy::Lookup { table_size: 256 });
+    assert_eq!(degree, strategy.degree());
+    run::<256, 5>(9);
+}
@@ -1,151 +1,115 @@
-
-use std::{marker::PhantomData};
+use halo2_tutorial::{field_chip, prover};
 
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{AssignedCell,Chip, Layouter, SimpleFloorPlanner},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
-    poly::Rotation,
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
 };
 
-
 use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
-use halo2_proofs::circuit::Region;
-use halo2_proofs::plonk::Fixed;
 
-#[derive(Clone)]
-struct Number<F: FieldExt>(AssignedCell<F, F>);
+use field_chip::{FieldChip, FieldConfig, NumericInstruction, Op};
 
-#[derive(Clone, Debug)]
-struct FieldConfig {
-    advice: [Column<Advice>;2],
-    instance: Column<Instance>,
-    s_mul: Selector,
+#[derive(Default)]
+struct MyCircuit<F: FieldExt> {
+    constant: F,
+    a: Option<F>,
+    b: Option<F>,
 }
 
-struct FieldChip<F: FieldExt>{
-    config: FieldConfig,
-    _marker: PhantomData<F>,
-}
 
-impl<F: FieldExt> Chip<F> for FieldChip<F> {
+impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FieldConfig;
-    type Loaded = ();
 
-    fn config(&self) -> &Self::Config {
-        &self.config
-    }
-
-    fn loaded(&self) -> &Self::Loaded {
-        &()
-    }
-}
+    type FloorPlanner = SimpleFloorPlanner;
 
-impl <F: FieldExt> FieldChip<F> {
-    fn construct (config: <Self as Chip<F>>::Config)->Self {
-        Self{
-            config,
-            _marker: PhantomData,
+    fn without_witnesses(&self) -> Self {
+        Self {
+            constant: self.constant,
+            a: None,
+            b: None,
         }
     }
-    fn configured(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>;2],
-        instance: Column<Instance>,
-        constant: Column<Fixed>) -> <Self as Chip<F>>::Config {
-        meta.enable_equality(instance);
-        meta.enable_constant(constant);
-        for col in &advice {
-            meta.enable_equality(*col);
-        }
-        let s_mul = meta.selector();
-
-        meta.create_gate("mul", |meta| {
-            let lhs = meta.query_advice(advice[0], Rotation::cur());
-            let rhs = meta.query_advice(advice[1], Rotation::cur());
-            let out = meta.query_advice(advice[0], Rotation::next());
-            let s_mul = meta.query_selector(s_mul);
-            vec![s_mul * (lhs * rhs - out)]
-        });
-
-        FieldConfig{
-            advice,instance,s_mul
-        }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column()];
+
+        let instance = meta.instance_column();
+
+        let constant = meta.fixed_column();
+
+        FieldChip::configure(meta, advice, instance, constant)
     }
-}
-trait NumericInstruction<F: FieldExt>: Chip<F> {
-    type Num;
 
-    fn load_private(&self, layouter: impl Layouter<F>, a: F) -> Result<Self::Num, Error>;
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let field_chip = FieldChip::construct(config);
 
-    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
 
-    fn mul(
-        &self,
-        layouter: impl Layouter<F>,
-        a: Self::Num,
-        b: Self::Num,
-    ) -> Result<Self::Num, Error>;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
 
+        let constant = field_chip.load_constant(layouter.namespace(|| "load constant"), self.constant)?;
 
-    // why need this???
-    fn expose_public(
-        &self,
-        layouter: impl Layouter<F>,
-        num: Self::Num,
-        row: usize,
-    ) -> Result<(), Error>;
-}
+        let ab = field_chip.mul(layouter.namespace(||"a * b"), a, b)?;
+        let absq = field_chip.mul(layouter.namespace(|| "ab * ab"), ab.clone(), ab)?;
 
-impl <F: FieldExt> NumericInstruction<F> for FieldChip<F> {
-    type Num = Number<F>;
+        let c = field_chip.mul(layouter.namespace(||"constant * absq"), constant, absq)?;
+
+        field_chip.expose_public(layouter.namespace(|| "field expose"), c, 0)
 
-    fn load_private(&self, mut layouter: impl Layouter<F>, a: F) -> Result<Self::Num, Error> {
-        let config = self.config();
-        layouter.assign_region(||"load private", |mut region|{
-            region.assign_advice(||"",config.advice[0], 0, ||Ok(a)).map(Number)
-        })
     }
+}
 
-    fn load_constant(&self, mut layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error> {
-        let config = self.config();
-        layouter.assign_region(||"load constant", |mut region|{
-            region.assign_advice_from_constant(||"constant input",config.advice[0], 0, constant).map(Number)
-        })
+/// Runs a single `FieldChip` operation on `a` and `b` and exposes the result.
+/// `tamper` is added to the honest output before it is assigned.
+struct OpCircuit<F: FieldExt> {
+    op: Op,
+    a: Option<F>,
+    b: Option<F>,
+    tamper: F,
+}
 
-    }
+impl<F: FieldExt> Circuit<F> for OpCircuit<F> {
+    type Config = FieldConfig;
 
-    fn expose_public(&self, mut layouter: impl Layouter<F>, num: Self::Num, row: usize) -> Result<(), Error> {
-       let config = self.config();
-        layouter.constrain_instance(num.0.cell(), config.instance, row)
-    }
+    type FloorPlanner = SimpleFloorPlanner;
 
+    fn without_witnesses(&self) -> Self {
+        Self {
+            op: self.op,
+            a: None,
+            b: None,
+            tamper: F::zero(),
+        }
+    }
 
-    fn mul(&self, mut layouter: impl Layouter<F>, a: Self::Num, b: Self::Num) -> Result<Self::Num, Error> {
-        let config = self.config();
-        layouter.assign_region(||"mul", |mut region: Region<'_, F>|{
-            config.s_mul.enable(&mut region, 0)?;
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
 
-            a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
-            b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let field_chip = FieldChip::construct(config);
 
-            let value = a.0.value().copied().unwrap() * b.0.value().copied().unwrap();
-          //  println!("{:?}", value);
+        let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
 
-            region.assign_advice(|| "lhs * rhs", config.advice[0], 1, || Ok(value)).map(Number)
+        let rhs = if self.op.is_unary() { None } else { Some(&b) };
+        let out = self.a.zip(self.b).map(|(a, b)| self.op.eval(a, b) + self.tamper);
+        let c = field_chip.assign_op(layouter.namespace(|| "op"), self.op, &a, rhs, out)?;
 
-        })
+        field_chip.expose_public(layouter.namespace(|| "field expose"), c, 0)
     }
 }
+
+/// Proves `c = (a - b) * (a + b) / d + (-a)^2`, using every instruction.
 #[derive(Default)]
-struct MyCircuit<F: FieldExt> {
-    constant: F,
-    a: F,
-    b: F
+struct ArithmeticCircuit<F: FieldExt> {
+    a: Option<F>,
+    b: Option<F>,
+    d: Option<F>,
 }
 
-
-impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+impl<F: FieldExt> Circuit<F> for ArithmeticCircuit<F> {
     type Config = FieldConfig;
 
     type FloorPlanner = SimpleFloorPlanner;
@@ -155,32 +119,73 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let advice = [meta.advice_column(), meta.advice_column()];
-
-        let instance = meta.instance_column();
-
-        let constant = meta.fixed_column();
-
-        FieldChip::configured(meta, advice, instance, constant)
+        MyCircuit::configure(meta)
     }
 
     fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
         let field_chip = FieldChip::construct(config);
 
         let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
-
         let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+        let d = field_chip.load_private(layouter.namespace(|| "load d"), self.d)?;
 
-        let constant = field_chip.load_constant(layouter.namespace(|| "load constant"), self.constant)?;
-
-        let ab = field_chip.mul(layouter.namespace(||"a * b"), a, b)?;
-        let absq = field_chip.mul(layouter.namespace(|| "ab * ab"), ab.clone(), ab)?;
-
-        let c = field_chip.mul(layouter.namespace(||"constant * absq"), constant, absq)?;
+        let diff = field_chip.sub(layouter.namespace(|| "a - b"), a.clone(), b.clone())?;
+        let sum = field_chip.add(layouter.namespace(|| "a + b"), a.clone(), b)?;
+        let prod = field_chip.mul(layouter.namespace(|| "(a - b) * (a + b)"), diff, sum)?;
+        let quot = field_chip.div(layouter.namespace(|| "prod / d"), prod, d)?;
+        let neg_a = field_chip.neg(layouter.namespace(|| "-a"), a)?;
+        let sq = field_chip.square(layouter.namespace(|| "(-a)^2"), neg_a)?;
+        let c = field_chip.add(layouter.namespace(|| "quot + sq"), quot, sq)?;
 
         field_chip.expose_public(layouter.namespace(|| "field expose"), c, 0)
+    }
+}
 
+fn op_test() {
+    let k = 4;
+    let a = Fp::from(12);
+    let b = Fp::from(5);
+
+    for op in [Op::Add, Op::Sub, Op::Mul, Op::Square, Op::Neg, Op::Inv] {
+        // The honest output is accepted.
+        let circuit = OpCircuit { op, a: Some(a), b: Some(b), tamper: Fp::zero() };
+        let public_input = vec![op.eval(a, b)];
+        let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{:?}", op);
+
+        // A tampered output is rejected by the gate, even though it matches
+        // the public input.
+        let circuit = OpCircuit { op, a: Some(a), b: Some(b), tamper: Fp::one() };
+        let public_input = vec![op.eval(a, b) + Fp::one()];
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err(), "{:?}", op);
     }
+
+    // Zero has no inverse.
+    let circuit = OpCircuit { op: Op::Inv, a: Some(Fp::zero()), b: Some(b), tamper: Fp::zero() };
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero()]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+fn arithmetic_test() {
+    let k = 5;
+    let a = Fp::from(9);
+    let b = Fp::from(4);
+    let d = Fp::from(13);
+    let c = (a - b) * (a + b) * Op::Inv.eval(d, Fp::zero()) + a.square();
+
+    let circuit = ArithmeticCircuit { a: Some(a), b: Some(b), d: Some(d) };
+    let prover = MockProver::run(k, &circuit, vec![vec![c]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A wrong public output is rejected.
+    let prover = MockProver::run(k, &circuit, vec![vec![c + Fp::one()]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Dividing by zero is rejected, whatever output is claimed.
+    let circuit = ArithmeticCircuit { a: Some(a), b: Some(b), d: Some(Fp::zero()) };
+    let prover = MockProver::run(k, &circuit, vec![vec![a.square()]]).unwrap();
+    assert!(prover.verify().is_err());
 }
 
 fn main() {
@@ -193,13 +198,17 @@ fn main() {
 
     let circuit = MyCircuit {
         constant,
-        a,
-        b,
+        a: Some(a),
+        b: Some(b),
     };
 
     let public_input = vec![c];
 
     let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
+    println!("{}", prover::round_trip(k, &circuit, &[public_input]).unwrap());
 
-}
\ No newline at end of file
+
+    op_test();
+    arithmetic_test();
+}
@@ -1,10 +1,10 @@
-mod table;
+use halo2_tutorial::{lt, prover};
 
 use std::{marker::PhantomData};
 
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{AssignedCell,Chip, Layouter, SimpleFloorPlanner},
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner},
     plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
     poly::Rotation,
 };
@@ -13,196 +13,9 @@ use halo2_proofs::{
 struct Number<F: FieldExt>(AssignedCell<F, F>);
 
 use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
-use halo2_proofs::circuit::Region;
-use halo2_proofs::plonk::{Expression, VirtualCells};
 
-pub trait Expr<F: FieldExt> {
-    fn expr(&self) -> Expression<F>;
-}
-
-#[macro_export]
-macro_rules! impl_expr {
-    ($type:ty) => {
-        impl<F: halo2_proofs::arithmetic::FieldExt> Expr<F> for $type {
-            #[inline]
-            fn expr(&self) -> Expression<F> {
-                Expression::Constant(F::from(*self as u64))
-            }
-        }
-    };
-}
-impl_expr!(bool);
-impl_expr!(u64);
-impl<F: FieldExt> Expr<F> for Expression<F> {
-    #[inline]
-    fn expr(&self) -> Expression<F> {
-        self.clone()
-    }
-}
-
-impl<F: FieldExt> Expr<F> for &Expression<F> {
-    #[inline]
-    fn expr(&self) -> Expression<F> {
-        (*self).clone()
-    }
-}
-impl<F: FieldExt> Expr<F> for i32 {
-    #[inline]
-    fn expr(&self) -> Expression<F> {
-        Expression::Constant(
-            F::from(self.unsigned_abs() as u64)
-                * if self.is_negative() {
-                -F::one()
-            } else {
-                F::one()
-            },
-        )
-    }
-}
-/// Given a bytes-representation of an expression, it computes and returns the
-/// single expression.
-fn expr_from_bytes<F: FieldExt, E: Expr<F>>(bytes: &[E]) -> Expression<F> {
-    let mut value = Expression::Constant(F::from(0));
-    let mut multiplier = F::one();
-    for byte in bytes.iter() {
-        value = value + byte.expr() * multiplier;
-        multiplier *= F::from(256);
-    }
-    value
-}
+use lt::{LtChip, LtConfig, LtInstruction};
 
-/// Instruction that the Lt chip needs to implement.
-pub trait LtInstruction<F: FieldExt> {
-    /// Assign the lhs and rhs witnesses to the Lt chip's region.
-    fn assign(
-        &self,
-        region: &mut Region<'_, F>,
-        offset: usize,
-        lhs: F,
-        rhs: F,
-    ) -> Result<(), Error>;
-}
-/// Config for the Lt chip.
-#[derive(Clone, Copy, Debug)]
-pub struct LtConfig<F, const N_BYTES: usize> {
-    /// Denotes the lt outcome. If lhs < rhs then lt == 1, otherwise lt == 0.
-    pub lt: Column<Advice>,
-    /// Denotes the bytes representation of the difference between lhs and rhs.
-    /// Note that the range of each byte is not checked by this config.
-    pub diff: [Column<Advice>; N_BYTES],
-    /// Denotes the range within which both lhs and rhs lie.
-    pub range: F,
-}
-
-impl<F: FieldExt, const N_BYTES: usize> LtConfig<F, N_BYTES> {
-    /// Returns an expression that denotes whether lhs < rhs, or not.
-    pub fn is_lt(&self, meta: &mut VirtualCells<F>, rotation: Option<Rotation>) -> Expression<F> {
-        meta.query_advice(self.lt, rotation.unwrap_or_else(Rotation::cur))
-    }
-}
-
-/// Restrict an expression to be a boolean.
-fn bool_check<F: FieldExt>(value: Expression<F>) -> Expression<F> {
-    range_check(value, 2)
-}
-
-/// Restrict an expression such that 0 <= word < range.
-fn range_check<F: FieldExt>(word: Expression<F>, range: usize) -> Expression<F> {
-    (1..range).fold(word.clone(), |acc, i| {
-        acc * (Expression::Constant(F::from(i as u64)) - word.clone())
-    })
-}
-
-#[derive(Clone, Debug)]
-pub struct LtChip<F, const N_BYTES: usize> {
-    config: LtConfig<F, N_BYTES>,
-}
-
-impl<F: FieldExt, const N_BYTES: usize> LtChip<F, N_BYTES> {
-    /// Configures the Lt chip.
-    pub fn configure(
-        meta: &mut ConstraintSystem<F>,
-        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
-        lhs: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
-        rhs: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
-    ) -> LtConfig<F, N_BYTES> {
-        let lt = meta.advice_column();
-        let diff = [(); N_BYTES].map(|_| meta.advice_column());
-        let range = F::from(2).pow(&[(N_BYTES * 8) as u64, 0, 0, 0]);
-
-        meta.create_gate("lt gate", |meta| {
-            let q_enable = q_enable(meta);
-            let lt = meta.query_advice(lt, Rotation::cur());
-
-            let diff_bytes = diff
-                .iter()
-                .map(|c| meta.query_advice(*c, Rotation::cur()))
-                .collect::<Vec<Expression<F>>>();
-
-            let check_a =
-                lhs(meta) - rhs(meta) - expr_from_bytes(&diff_bytes) + (lt.clone() * range);
-
-            let check_b = bool_check(lt);
-
-            [check_a, check_b]
-                .into_iter()
-                .map(move |poly| q_enable.clone() * poly)
-        });
-
-        LtConfig { lt, diff, range }
-    }
-
-    /// Constructs a Lt chip given a config.
-    pub fn construct(config: LtConfig<F, N_BYTES>) -> LtChip<F, N_BYTES> {
-        LtChip { config }
-    }
-}
-impl<F: FieldExt, const N_BYTES: usize> LtInstruction<F> for LtChip<F, N_BYTES> {
-    fn assign(
-        &self,
-        region: &mut Region<'_, F>,
-        offset: usize,
-        lhs: F,
-        rhs: F,
-    ) -> Result<(), Error> {
-        let config = self.config();
-
-        let lt = lhs < rhs;
-        region.assign_advice(
-            || "lt chip: lt",
-            config.lt,
-            offset,
-            || Ok(F::from(lt as u64)),
-        )?;
-
-        let diff = (lhs - rhs) + (if lt { config.range } else { F::zero() });
-        let diff_bytes = diff.to_repr();
-        let diff_bytes = diff_bytes.as_ref();
-        for (idx, diff_column) in config.diff.iter().enumerate() {
-            region.assign_advice(
-                || format!("lt chip: diff byte {}", idx),
-                *diff_column,
-                offset,
-                || Ok(F::from(diff_bytes[idx] as u64)),
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-impl<F: FieldExt, const N_BYTES: usize> Chip<F> for LtChip<F, N_BYTES> {
-    type Config = LtConfig<F, N_BYTES>;
-    type Loaded = ();
-
-    fn config(&self) -> &Self::Config {
-        &self.config
-    }
-
-    fn loaded(&self) -> &Self::Loaded {
-        &()
-    }
-}
 macro_rules! try_test_circuit {
         ($values:expr, $checks:expr, $result:expr) => {{
             // let k = usize::BITS - $values.len().leading_zeros();
@@ -211,141 +24,149 @@ macro_rules! try_test_circuit {
             // correct k (without the extra + 2).
             let k = usize::BITS - $values.len().leading_zeros() + 2;
             let circuit = TestCircuit::<Fp> {
-                values: Some($values),
-                checks: Some($checks),
+                values: $values.into_iter().map(Some).collect(),
+                checks: $checks.into_iter().map(Some).collect(),
                 _marker: PhantomData,
             };
             let prover = MockProver::<Fp>::run(k, &circuit, vec![]).unwrap();
-            assert_eq!(prover.verify(), $result);
+            let result = $result;
+            assert_eq!(prover.verify(), result);
+            if result.is_ok() {
+                println!("{}", prover::round_trip(k, &circuit, &[]).unwrap());
+            }
         }};
     }
-fn sort_test() {
-    #[derive(Clone, Debug)]
-    struct TestCircuitConfig<F> {
-        q_enable: Selector,
-        value: Column<Advice>,
-        check: Column<Advice>,
-        lt: LtConfig<F, 8>,
-    }
-
-    #[derive(Default)]
-    struct TestCircuit<F: FieldExt> {
-        values: Option<Vec<u64>>,
-        // checks[i] = lt(values[i + 1], values[i])
-        checks: Option<Vec<bool>>,
-        _marker: PhantomData<F>,
-    }
-
-    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
-        type Config = TestCircuitConfig<F>;
-        type FloorPlanner = SimpleFloorPlanner;
 
-        fn without_witnesses(&self) -> Self {
-            Self::default()
+#[derive(Clone, Debug)]
+pub(crate) struct TestCircuitConfig<F> {
+    q_enable: Selector,
+    value: Column<Advice>,
+    check: Column<Advice>,
+    lt: LtConfig<F, 8>,
+}
+
+// The number of values fixes the circuit's shape, so it is known even
+// when the values themselves are not.
+pub(crate) struct TestCircuit<F: FieldExt> {
+    pub(crate) values: Vec<Option<u64>>,
+    // checks[i] = lt(values[i + 1], values[i])
+    pub(crate) checks: Vec<Option<bool>>,
+    pub(crate) _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+    type Config = TestCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.values.len()],
+            checks: vec![None; self.checks.len()],
+            _marker: PhantomData,
         }
+    }
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let q_enable = meta.complex_selector();
-            let value = meta.advice_column();
-            let check = meta.advice_column();
-
-            let lt = LtChip::configure(
-                meta,
-                |meta| meta.query_selector(q_enable),
-                |meta| meta.query_advice(value, Rotation::prev()),
-                |meta| meta.query_advice(value, Rotation::cur()),
-            );
-
-            let config = Self::Config {
-                q_enable,
-                value,
-                check,
-                lt,
-            };
-
-            meta.create_gate("check is_lt between adjacent rows", |meta| {
-                let q_enable = meta.query_selector(q_enable);
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_enable = meta.complex_selector();
+        let value = meta.advice_column();
+        let check = meta.advice_column();
 
-                // This verifies lt(value::cur, value::next) is calculated correctly
-                let check = meta.query_advice(config.check, Rotation::cur());
+        let lt = LtChip::configure(
+            meta,
+            |meta| meta.query_selector(q_enable),
+            |meta| meta.query_advice(value, Rotation::prev()),
+            |meta| meta.query_advice(value, Rotation::cur()),
+        );
 
-                vec![q_enable * (config.lt.is_lt(meta, None) - check)]
-            });
+        let config = Self::Config {
+            q_enable,
+            value,
+            check,
+            lt,
+        };
 
-            config
-        }
+        meta.create_gate("check is_lt between adjacent rows", |meta| {
+            let q_enable = meta.query_selector(q_enable);
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            let chip = LtChip::construct(config.lt);
+            // This verifies lt(value::cur, value::next) is calculated correctly
+            let check = meta.query_advice(config.check, Rotation::cur());
 
-            let sorted_values = Some(bubble_sort(self.values.as_ref().unwrap()));
+            vec![q_enable * (config.lt.is_lt(meta, None) - check)]
+        });
 
-            let values: Vec<_> = sorted_values
-                .as_ref()
-                .map(|values| values.iter().map(|value| F::from(*value)).collect())
-                .ok_or(Error::Synthesis)?;
-            let checks = self.checks.as_ref().ok_or(Error::Synthesis)?;
-            let (first_value, values) = values.split_at(1);
-            let first_value = first_value[0];
+        config
+    }
 
-            layouter.assign_region(
-                || "witness",
-                |mut region| {
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = LtChip::construct(config.lt);
+
+        let values: Vec<Option<F>> = match self.values.iter().copied().collect::<Option<Vec<_>>>() {
+            Some(values) => bubble_sort(&values).into_iter().map(|value| Some(F::from(value))).collect(),
+            None => vec![None; self.values.len()],
+        };
+        let checks = &self.checks;
+        let (first_value, values) = values.split_at(1);
+        let first_value = first_value[0];
+
+        layouter.assign_region(
+            || "witness",
+            |mut region| {
+                region.assign_advice(
+                    || "first row value",
+                    config.value,
+                    0,
+                    || first_value.ok_or(Error::Synthesis),
+                )?;
+
+                let mut value_prev = first_value;
+                for (idx, (value, check)) in values.iter().zip(checks).enumerate() {
+                    config.q_enable.enable(&mut region, idx + 1)?;
                     region.assign_advice(
-                        || "first row value",
+                        || "check",
+                        config.check,
+                        idx + 1,
+                        || check.map(|check| F::from(check as u64)).ok_or(Error::Synthesis),
+                    )?;
+                    region.assign_advice(
+                        || "value",
                         config.value,
-                        0,
-                        || Ok(first_value),
+                        idx + 1,
+                        || value.ok_or(Error::Synthesis),
                     )?;
+                    chip.assign(&mut region, idx + 1, value_prev, *value)?;
 
-                    let mut value_prev = first_value;
-                    for (idx, (value, check)) in values.iter().zip(checks).enumerate() {
-                        config.q_enable.enable(&mut region, idx + 1)?;
-                        region.assign_advice(
-                            || "check",
-                            config.check,
-                            idx + 1,
-                            || Ok(F::from(*check as u64)),
-                        )?;
-                        region.assign_advice(
-                            || "value",
-                            config.value,
-                            idx + 1,
-                            || Ok(*value),
-                        )?;
-                        chip.assign(&mut region, idx + 1, value_prev, *value)?;
-
-                        value_prev = *value;
-                    }
+                    value_prev = *value;
+                }
 
-                    Ok(())
-                },
-            )
-        }
+                Ok(())
+            },
+        )
     }
+}
 
-    fn bubble_sort(numbers: &Vec<u64>) -> Vec<u64> {
-        let mut temp;
-        let mut target = numbers.clone();
-        let length = numbers.len();
+pub(crate) fn bubble_sort(numbers: &Vec<u64>) -> Vec<u64> {
+    let mut temp;
+    let mut target = numbers.clone();
+    let length = numbers.len();
 
-        for _ in 0..length {
-            for j in 0..length - 1 {
-                if target[j] > target[j + 1] {
-                    temp = target[j + 1];
-                    target[j + 1] = target[j];
-                    target[j] = temp;
-                }
+    for _ in 0..length {
+        for j in 0..length - 1 {
+            if target[j] > target[j + 1] {
+                temp = target[j + 1];
+                target[j + 1] = target[j];
+                target[j] = temp;
             }
         }
-
-        target
     }
 
+    target
+}
+
+fn sort_test() {
     try_test_circuit!(vec![9, 4, 6, 2, 1], vec![true, true, true, true], Ok(()));
 
 }
@@ -0,0 +1,320 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    arithmetic::{best_fft, best_multiexp, CurveAffine},
+    pairing::arithmetic::Engine,
+    pairing::bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine, G1, G2},
+    pairing::group::{prime::PrimeCurveAffine, Curve, Group, GroupEncoding},
+    poly::{commitment::Params, EvaluationDomain},
+};
+use rand_core::OsRng;
+
+/// The sections of a snarkjs `.ptau` file that are read here. The others
+/// (alpha and beta powers, contributions) only matter to Groth16.
+const HEADER: u32 = 1;
+const TAU_G1: u32 = 2;
+const TAU_G2: u32 = 3;
+
+/// Bytes per base field element.
+const N8: usize = 32;
+
+/// Why a powers-of-tau file could not be used.
+#[derive(Debug)]
+pub enum SrsError {
+    Io(io::Error),
+    /// The file is not a BN254 `.ptau` file, or a section has the wrong size.
+    Format(String),
+    /// The ceremony was run for `2^power` powers, fewer than `k` needs.
+    Degree { power: u32, k: u32 },
+    /// Point `index` of `section` is not on the curve, or not in its
+    /// subgroup of prime order.
+    Encoding { section: &'static str, index: usize },
+    /// The G1 powers are not successive powers of the tau in G2.
+    Pairing,
+}
+
+impl fmt::Display for SrsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SrsError::Io(err) => write!(f, "i/o error: {}", err),
+            SrsError::Format(msg) => write!(f, "bad ptau file: {}", msg),
+            SrsError::Degree { power, k } => write!(f, "ceremony holds 2^{} powers, k = {} needs 2^{}", power, k, k),
+            SrsError::Encoding { section, index } => write!(f, "{} point {} is not in the prime-order group", section, index),
+            SrsError::Pairing => write!(f, "tau in G1 and G2 are inconsistent"),
+        }
+    }
+}
+
+impl From<io::Error> for SrsError {
+    fn from(err: io::Error) -> Self {
+        SrsError::Io(err)
+    }
+}
+
+/// Loads the first `2^k` powers of tau from the `.ptau` file at `path`, as
+/// parameters for circuits of `2^k` rows.
+///
+/// Every point read is checked to be in the prime-order group, and one pairing check on
+/// a random combination of the powers checks they are powers of the same
+/// tau as the G2 point the verifier uses.
+pub fn load(path: &Path, k: u32) -> Result<Params<G1Affine>, SrsError> {
+    let mut file = BufReader::new(File::open(path)?);
+    let sections = read_sections(&mut file)?;
+
+    let (offset, _) = section(&sections, HEADER)?;
+    file.seek(SeekFrom::Start(offset))?;
+    if read_u32(&mut file)? as usize != N8 {
+        return Err(SrsError::Format("field elements are not 32 bytes".to_string()));
+    }
+    let mut q = [0u8; N8];
+    file.read_exact(&mut q)?;
+    if q != modulus() {
+        return Err(SrsError::Format("not a BN254 ceremony".to_string()));
+    }
+    let power = read_u32(&mut file)?;
+    if k > power {
+        return Err(SrsError::Degree { power, k });
+    }
+
+    // The G1 section holds twice as many powers as the G2 section.
+    let (offset, size) = section(&sections, TAU_G1)?;
+    if size != ((2u64 << power) - 1) * 2 * N8 as u64 {
+        return Err(SrsError::Format("tau G1 section has the wrong size".to_string()));
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    let r_inv = montgomery_r().invert().unwrap();
+    // Two powers are needed to check the G2 point even when `k` is 0.
+    let n = 1usize << k;
+    let mut g = (0..n.max(2))
+        .map(|index| read_g1(&mut file, &r_inv)?.ok_or(SrsError::Encoding { section: "tau G1", index }))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (offset, size) = section(&sections, TAU_G2)?;
+    if size != (1u64 << power) * 4 * N8 as u64 {
+        return Err(SrsError::Format("tau G2 section has the wrong size".to_string()));
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    let g2 = read_g2(&mut file, &r_inv)?.ok_or(SrsError::Encoding { section: "tau G2", index: 0 })?;
+    let s_g2 = read_g2(&mut file, &r_inv)?.ok_or(SrsError::Encoding { section: "tau G2", index: 1 })?;
+
+    if g[0] != G1Affine::generator() || g2 != G2Affine::generator() {
+        return Err(SrsError::Format("powers do not start at the generators".to_string()));
+    }
+    check_powers(&g, &s_g2)?;
+    g.truncate(n);
+
+    // `Params` has no public constructor, so the powers go through its own
+    // encoding: `k`, the powers, their Lagrange basis and then `s * G2`.
+    let mut bytes = k.to_le_bytes().to_vec();
+    for point in g.iter().chain(lagrange(&g, k).iter()) {
+        bytes.extend_from_slice(point.to_bytes().as_ref());
+    }
+    bytes.extend_from_slice(s_g2.to_bytes().as_ref());
+    Ok(Params::read(&mut &bytes[..])?)
+}
+
+/// Writes a `.ptau` file holding the powers of a known `tau`, laid out as a
+/// ceremony would. Only for tests: anyone who knows `tau` can forge proofs.
+pub fn write_unsafe(path: &Path, power: u32, tau: Fr) -> Result<(), SrsError> {
+    let powers = |count: u64| {
+        let mut power = Fr::one();
+        (0..count).map(move |_| {
+            let current = power;
+            power *= tau;
+            current
+        })
+    };
+
+    let mut header = (N8 as u32).to_le_bytes().to_vec();
+    header.extend_from_slice(&modulus());
+    header.extend_from_slice(&power.to_le_bytes());
+    header.extend_from_slice(&power.to_le_bytes());
+
+    let mut tau_g1 = vec![];
+    for s in powers((2u64 << power) - 1) {
+        let point = (G1Affine::generator() * s).to_affine();
+        let coordinates = point.coordinates().unwrap();
+        write_fq(&mut tau_g1, coordinates.x());
+        write_fq(&mut tau_g1, coordinates.y());
+    }
+
+    let mut tau_g2 = vec![];
+    for s in powers(1u64 << power) {
+        write_g2(&mut tau_g2, &(G2Affine::generator() * s).to_affine());
+    }
+
+    let mut file = BufWriter::new(File::create(path)?);
+    file.write_all(b"ptau")?;
+    file.write_all(&1u32.to_le_bytes())?;
+    file.write_all(&3u32.to_le_bytes())?;
+    for (id, data) in [(HEADER, header), (TAU_G1, tau_g1), (TAU_G2, tau_g2)] {
+        file.write_all(&id.to_le_bytes())?;
+        file.write_all(&(data.len() as u64).to_le_bytes())?;
+        file.write_all(&data)?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// Reads the file header and returns the `(id, offset, size)` of every
+/// section, without reading the sections themselves.
+fn read_sections<R: Read + Seek>(reader: &mut R) -> Result<Vec<(u32, u64, u64)>, SrsError> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"ptau" {
+        return Err(SrsError::Format("missing ptau magic".to_string()));
+    }
+    let _version = read_u32(reader)?;
+    let count = read_u32(reader)?;
+
+    let mut sections = vec![];
+    for _ in 0..count {
+        let id = read_u32(reader)?;
+        let mut size = [0u8; 8];
+        reader.read_exact(&mut size)?;
+        let size = u64::from_le_bytes(size);
+        let offset = reader.stream_position()?;
+        sections.push((id, offset, size));
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    Ok(sections)
+}
+
+fn section(sections: &[(u32, u64, u64)], id: u32) -> Result<(u64, u64), SrsError> {
+    sections
+        .iter()
+        .find(|(section, _, _)| *section == id)
+        .map(|(_, offset, size)| (*offset, *size))
+        .ok_or_else(|| SrsError::Format(format!("missing section {}", id)))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// The BN254 base field modulus, little-endian.
+fn modulus() -> [u8; N8] {
+    let mut q = [0u8; N8];
+    q.copy_from_slice((-Fq::one()).to_repr().as_ref());
+    // q - 1 is even, so adding one never carries.
+    q[0] += 1;
+    q
+}
+
+/// `2^256 mod q`. snarkjs stores field elements multiplied by it.
+fn montgomery_r() -> Fq {
+    Fq::from(2).pow_vartime(&[256u64])
+}
+
+/// Reads one base field element in snarkjs' Montgomery form, given the
+/// inverse of `montgomery_r`, or `None` if it is not below the modulus.
+fn read_fq<R: Read>(reader: &mut R, r_inv: &Fq) -> io::Result<Option<Fq>> {
+    let mut repr = <Fq as PrimeField>::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+    Ok(Option::<Fq>::from(Fq::from_repr(repr)).map(|x| x * r_inv))
+}
+
+fn write_fq(out: &mut Vec<u8>, x: &Fq) {
+    out.extend_from_slice((*x * montgomery_r()).to_repr().as_ref());
+}
+
+/// Reads an uncompressed G1 point, or `None` if it is not a point of the
+/// curve other than the identity. G1 has cofactor 1, so that is all it takes
+/// to be in the group.
+fn read_g1<R: Read>(reader: &mut R, r_inv: &Fq) -> io::Result<Option<G1Affine>> {
+    let x = read_fq(reader, r_inv)?;
+    let y = read_fq(reader, r_inv)?;
+    Ok(x.zip(y)
+        .and_then(|(x, y)| Option::from(G1Affine::from_xy(x, y)))
+        .filter(|point: &G1Affine| !bool::from(point.is_identity())))
+}
+
+/// Reads an uncompressed G2 point, with the same checks as `read_g1` plus a
+/// subgroup check, since G2 has points outside the group of order `r`.
+fn read_g2<R: Read>(reader: &mut R, r_inv: &Fq) -> io::Result<Option<G2Affine>> {
+    let mut c = [None; 4];
+    for c in c.iter_mut() {
+        *c = read_fq(reader, r_inv)?;
+    }
+    Ok(match c {
+        [Some(x0), Some(x1), Some(y0), Some(y1)] => Option::from(G2Affine::from_xy(
+            Fq2 { c0: x0, c1: x1 },
+            Fq2 { c0: y0, c1: y1 },
+        ))
+        .filter(|point: &G2Affine| !bool::from(point.is_identity()) && in_subgroup(point)),
+        _ => None,
+    })
+}
+
+/// Whether `[r] point` is the identity. Multiplying by an `Fr` would reduce
+/// `r` to zero, so this adds `point` to `[r - 1] point`, walking the bits of
+/// `r - 1` by hand.
+fn in_subgroup(point: &G2Affine) -> bool {
+    let r_minus_one = (-Fr::one()).to_repr();
+    let mut acc = G2::identity();
+    for byte in r_minus_one.as_ref().iter().rev() {
+        for bit in (0..8).rev() {
+            acc = acc.double();
+            if (byte >> bit) & 1 == 1 {
+                acc += *point;
+            }
+        }
+    }
+    bool::from((acc + *point).is_identity())
+}
+
+/// Writes an uncompressed G2 point as a ceremony file holds it.
+pub fn write_g2(out: &mut Vec<u8>, point: &G2Affine) {
+    let (x, y) = g2_coordinates(point);
+    for c in x.iter().chain(y.iter()) {
+        write_fq(out, c);
+    }
+}
+
+fn g2_coordinates(point: &G2Affine) -> ([Fq; 2], [Fq; 2]) {
+    let coordinates = point.coordinates().unwrap();
+    let (x, y) = (coordinates.x(), coordinates.y());
+    ([x.c0, x.c1], [y.c0, y.c1])
+}
+
+/// Checks `g[i + 1] = s * g[i]` for every `i`, where `s_g2 = s * G2`, with a
+/// single pair of pairings: for a random `r`, the sums `sum r^i g[i + 1]` and
+/// `sum r^i g[i]` only differ by `s` if every pair does.
+fn check_powers(g: &[G1Affine], s_g2: &G2Affine) -> Result<(), SrsError> {
+    let r = Fr::random(OsRng);
+    let mut coeffs = vec![Fr::one(); g.len() - 1];
+    for i in 1..coeffs.len() {
+        coeffs[i] = coeffs[i - 1] * r;
+    }
+    let shifted = best_multiexp(&coeffs, &g[1..]).to_affine();
+    let unshifted = best_multiexp(&coeffs, &g[..g.len() - 1]).to_affine();
+    if Bn256::pairing(&shifted, &G2Affine::generator()) != Bn256::pairing(&unshifted, s_g2) {
+        return Err(SrsError::Pairing);
+    }
+    Ok(())
+}
+
+/// The commitments to the Lagrange basis of the `2^k`-th roots of unity:
+/// the inverse FFT of the powers `[tau^i] G1`.
+fn lagrange(g: &[G1Affine], k: u32) -> Vec<G1Affine> {
+    let n_inv = Fr::from(g.len() as u64).invert().unwrap();
+    let omega_inv = EvaluationDomain::<Fr>::new(1, k).get_omega_inv();
+
+    let mut points: Vec<G1> = g.iter().map(|point| point.to_curve()).collect();
+    best_fft(&mut points, omega_inv, k);
+    for point in points.iter_mut() {
+        *point = *point * n_inv;
+    }
+
+    let mut affine = vec![G1Affine::identity(); points.len()];
+    G1::batch_normalize(&points, &mut affine);
+    affine
+}
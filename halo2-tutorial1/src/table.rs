@@ -15,9 +15,9 @@ use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
 use halo2_proofs::circuit::floor_planner::V1;
 use halo2_proofs::plonk::Assigned;
 
-#[derive(Clone, Copy)]
-pub(super) struct RangTableConfig<F: FieldExt> {
-    pub(super) col_value: TableColumn,
+#[derive(Clone, Copy, Debug)]
+pub struct RangTableConfig<F: FieldExt> {
+    pub col_value: TableColumn,
     _marker: PhantomData<F>,
 }
 
@@ -41,9 +41,7 @@ impl<F: FieldExt> RangTableConfig<F> {
             |mut table| {
                 let mut offset = 0;
 
-                println!("load values: {:?}", values);
                 for el in values.clone() {
-                    println!("assign cell, offset {:?}, element: {:?}", offset, el);
                     table.assign_cell(
                         || "assign table cell",
                         self.col_value,
@@ -59,15 +57,16 @@ impl<F: FieldExt> RangTableConfig<F> {
     }
 }
 #[derive(Clone, Copy)]
-struct TestConfig<F: FieldExt> {
+pub struct TestConfig<F: FieldExt> {
     a: Column<Advice>,
     q_selector: Selector,
     lookup_table: RangTableConfig<F>,
 }
 
+/// Looks `value` up in the table `[0, 3, 4, 5, 7, 8, 15, 16, 18]`.
 #[derive(Default)]
-struct MyCircuit<F: FieldExt> {
-    value: F
+pub struct MyCircuit<F: FieldExt> {
+    pub value: Option<F>,
 }
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
@@ -117,7 +116,7 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
                     || "assign a",
                     config.a,
                     0,
-                    || Ok(self.value),
+                    || self.value.ok_or(Error::Synthesis),
                 )
             },
         )?;
@@ -128,7 +127,7 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
 pub fn Test() {
     let test_value: u64 = 8;
 
-    let circuit = MyCircuit::<Fp> { value: Fp::from(test_value as u64).into() };
+    let circuit = MyCircuit::<Fp> { value: Some(Fp::from(test_value as u64)) };
 
     let prover = MockProver::run(4, &circuit, vec![]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
@@ -1,5 +1,7 @@
 extern crate core;
 
+use halo2_tutorial::prover;
+
 use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
 use std::marker::PhantomData;
 
@@ -16,7 +18,7 @@ trait NumericInstructions<F: FieldExt>: Chip<F> {
     type Num;
 
     /// Loads a number into the circuit as a private input.
-    fn load_private(&self, layouter: impl Layouter<F>, a: F) -> Result<Self::Num, Error>;
+    fn load_private(&self, layouter: impl Layouter<F>, a: Option<F>) -> Result<Self::Num, Error>;
 
     /// Loads a number into the circuit as a fixed constant.
     fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
@@ -73,7 +75,10 @@ struct FieldConfig {
     // any constraints on cells where `NumericInstructions::mul` is not being used.
     // This is important when building larger circuits, where columns are used by
     // multiple sets of instructions.
-    ss: Selector,
+    s_mul: Selector,
+
+    // The addition gate gets its own selector for the same reason.
+    s_add: Selector,
 }
 
 impl<F: FieldExt> FieldChip<F> {
@@ -95,46 +100,48 @@ impl<F: FieldExt> FieldChip<F> {
         for column in &advice {
             meta.enable_equality(*column);
         }
-        let ss = meta.selector();
-
-        // // Define our multiplication gate!
-        // meta.create_gate("mul", |meta| {
-        //     // To implement multiplication, we need three advice cells and a selector
-        //     // cell. We arrange them like so:
-        //     //
-        //     // | a0  | a1  | ss |
-        //     // |-----|-----|----|
-        //     // | lhs | rhs | ss |
-        //     // | out |     |    |
-        //     //
-        //     // Gates may refer to any relative offsets we want, but each distinct
-        //     // offset adds a cost to the proof. The most common offsets are 0 (the
-        //     // current row), 1 (the next row), and -1 (the previous row), for which
-        //     // `Rotation` has specific constructors.
-        //     let lhs = meta.query_advice(advice[0], Rotation::cur());
-        //     let rhs = meta.query_advice(advice[1], Rotation::cur());
-        //     let out = meta.query_advice(advice[0], Rotation::next());
-        //     let ss = meta.query_selector(ss);
-
-        //     vec![ss * (lhs * rhs - out)]
-        // });
+        let s_mul = meta.selector();
+        let s_add = meta.selector();
+
+        // Define our multiplication gate!
+        meta.create_gate("mul", |meta| {
+            // To implement multiplication, we need three advice cells and a selector
+            // cell. We arrange them like so:
+            //
+            // | a0  | a1  | s_mul |
+            // |-----|-----|-------|
+            // | lhs | rhs | s_mul |
+            // | out |     |       |
+            //
+            // Gates may refer to any relative offsets we want, but each distinct
+            // offset adds a cost to the proof. The most common offsets are 0 (the
+            // current row), 1 (the next row), and -1 (the previous row), for which
+            // `Rotation` has specific constructors.
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mul = meta.query_selector(s_mul);
+
+            vec![s_mul * (lhs * rhs - out)]
+        });
 
         meta.create_gate("add", |meta| {
-            // | a0  | a1  | ss |
-            // |-----|-----|----|
-            // | lhs | rhs | ss |
-            // | out |     |    |
+            // | a0  | a1  | s_add |
+            // |-----|-----|-------|
+            // | lhs | rhs | s_add |
+            // | out |     |       |
             let lhs = meta.query_advice(advice[0], Rotation::cur());
             let rhs = meta.query_advice(advice[1], Rotation::cur());
             let out = meta.query_advice(advice[0], Rotation::next());
-            let ss = meta.query_selector(ss);
-            vec![ss * (lhs + rhs - out)]
+            let s_add = meta.query_selector(s_add);
+            vec![s_add * (lhs + rhs - out)]
         });
 
         FieldConfig {
             advice,
             instance,
-            ss,
+            s_mul,
+            s_add,
         }
     }
 }
@@ -166,7 +173,7 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
     fn load_private(
         &self,
         mut layouter: impl Layouter<F>,
-        value: F,
+        value: Option<F>,
     ) -> Result<Self::Num, Error> {
         let config = self.config();
 
@@ -174,7 +181,7 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
             || "load private",
             |mut region| {
                 region
-                    .assign_advice(|| "private input", config.advice[0], 0, || Ok(value))
+                    .assign_advice(|| "private input", config.advice[0], 0, || value.ok_or(Error::Synthesis))
                     .map(Number)
             },
         )
@@ -211,7 +218,7 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
                 // We only want to use a single multiplication gate in this region,
                 // so we enable it at region offset 0; this means it will constrain
                 // cells at offsets 0 and 1.
-                config.ss.enable(&mut region, 0)?;
+                config.s_mul.enable(&mut region, 0)?;
 
                 // The inputs we've been given could be located anywhere in the circuit,
                 // but we can only rely on relative offsets inside this region. So we
@@ -222,12 +229,14 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
 
                 // Now we can assign the multiplication result, which is to be assigned
                 // into the output position.
-                let value = a.0.value().copied().unwrap() * b.0.value().copied().unwrap();
+                // The inputs have no values during key generation, so neither
+                // does the output.
+                let value = a.0.value().zip(b.0.value()).map(|(a, b)| *a * *b);
 
                 // Finally, we do the assignment to the output, returning a
                 // variable to be used in another part of the circuit.
                 region
-                    .assign_advice(|| "lhs * rhs", config.advice[0], 1, || Ok(value))
+                    .assign_advice(|| "lhs * rhs", config.advice[0], 1, || value.ok_or(Error::Synthesis))
                     .map(Number)
             },
         )
@@ -243,12 +252,12 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
         layouter.assign_region(
             || "add",
             |mut region: Region<'_, F>| {
-                config.ss.enable(&mut region, 0)?;
+                config.s_add.enable(&mut region, 0)?;
                 a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
                 b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
-                let value = a.0.value().copied().unwrap() + b.0.value().copied().unwrap();
+                let value = a.0.value().zip(b.0.value()).map(|(a, b)| *a + *b);
                 region
-                    .assign_advice(|| "lhs + rhs", config.advice[0], 1, || Ok(value))
+                    .assign_advice(|| "lhs + rhs", config.advice[0], 1, || value.ok_or(Error::Synthesis))
                     .map(Number)
             },
         )
@@ -276,8 +285,8 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
 #[derive(Default)]
 struct MyCircuit<F: FieldExt> {
     constant: F,
-    a: F,
-    b: F,
+    a: Option<F>,
+    b: Option<F>,
 }
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
@@ -286,7 +295,12 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // The constant is part of the circuit, not the witness.
+        Self {
+            constant: self.constant,
+            a: None,
+            b: None,
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -344,8 +358,8 @@ fn main() {
     // Instantiate the circuit with the private inputs.
     let circuit = MyCircuit {
         constant,
-        a: a,
-        b: b,
+        a: Some(a),
+        b: Some(b),
     };
 
     println!("# Prover is expected to return OK.");
@@ -355,6 +369,9 @@ fn main() {
     // Given the correct public input, our circuit will verify.
     let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
+
+    println!("# Real proof is expected to verify.");
+    println!("{}", prover::round_trip(k, &circuit, &[public_inputs]).unwrap());
     println!("# Done.");
 
     // ANCHOR_END: test-circuit
@@ -1,19 +1,24 @@
- use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
+use halo2_tutorial::{expr, prover};
+
+use halo2_proofs::{dev::{MockProver, VerifyFailure}, pairing::bn256::Fr as Fp};
 use std::marker::PhantomData;
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{AssignedCell, Layouter, SimpleFloorPlanner},
-    plonk::{Advice, Circuit, Column, Instance, ConstraintSystem, Error, Expression, Selector},
+    plonk::{Advice, Circuit, Column, Instance, ConstraintSystem, Error, Selector},
     poly::Rotation,
 };
- use halo2_proofs::circuit::Cell;
 
- #[derive(Debug, Clone)]
+use expr::{is_zero, is_zero_check, select};
+
+#[derive(Debug, Clone)]
 struct FunctionConfig {
     selector: Selector,
-    cond: Column<Advice>,
-    thenval: Column<Advice>,
-    elseval: Column<Advice>,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    /// Witness for `1 / (a - b)`, or anything when `a == b`.
+    a_minus_b_inv: Column<Advice>,
     output: Column<Advice>,
     instance: Column<Instance>,
 }
@@ -34,40 +39,46 @@ impl<F: FieldExt> FunctionChip<F> {
 
     pub fn configure(meta: &mut ConstraintSystem<F>) -> FunctionConfig {
         let selector = meta.selector();
-        let cond = meta.advice_column();
-        let thenval = meta.advice_column();
-        let elseval = meta.advice_column();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let c = meta.advice_column();
+        let a_minus_b_inv = meta.advice_column();
         let output = meta.advice_column();
         let instance = meta.instance_column();
 
-        /**/
-        meta.enable_equality(cond);
-        meta.enable_equality(thenval);
-        meta.enable_equality(elseval);
         meta.enable_equality(output);
-        //for the solution against ColumnNotInPermutation, you should consider add all equality check
-       // meta.enable_equality(instance);
+        meta.enable_equality(instance);
 
         meta.create_gate("f(a, b, c) = if a == b {c} else {a - b}", |meta| {
+            //
+            // a | b | c | a_minus_b_inv | output | selector
+            //
             let s = meta.query_selector(selector);
-            let cond = meta.query_advice(cond, Rotation::cur());
-            let thenval = meta.query_advice(thenval, Rotation::cur());
-            let elseval = meta.query_advice(elseval, Rotation::cur());
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            let a_minus_b_inv = meta.query_advice(a_minus_b_inv, Rotation::cur());
             let output = meta.query_advice(output, Rotation::cur());
 
+            let diff = a - b;
 
-            let one = Expression::Constant(F::one());
+            // is_eq is 1 when a == b, whatever the inverse witness is. When
+            // a != b the first constraint forces the inverse to be correct,
+            // which makes is_eq 0.
+            let is_eq = is_zero(&diff, &a_minus_b_inv);
 
             vec![
-                s * ( cond.clone() * thenval + (one - cond) * elseval - output )
+                s.clone() * is_zero_check(&diff, &a_minus_b_inv),
+                s * (select(is_eq, c, diff) - output),
             ]
         });
 
         FunctionConfig {
             selector,
-            cond,
-            thenval,
-            elseval,
+            a,
+            b,
+            c,
+            a_minus_b_inv,
             output,
             instance,
         }
@@ -82,35 +93,37 @@ impl<F: FieldExt> FunctionChip<F> {
         layouter.constrain_instance(cell.cell(), self.config.instance, row)
     }
 
+    /// Assigns `f(a, b, c)`. The inverse witness is computed honestly unless
+    /// `forged_inv` is given, and the output follows whatever condition that
+    /// witness implies unless `forged_output` is given.
     pub fn assign(
         &self,
         mut layouter: impl Layouter<F>,
-        cond: F,
-        thenval: F,
-        elseval: F,
+        a: Option<F>,
+        b: Option<F>,
+        c: Option<F>,
+        forged_inv: Option<F>,
+        forged_output: Option<F>,
     ) -> Result<AssignedCell<F, F>, Error> {
         layouter.assign_region(
-            || "f(cond, thenval, elseval) = if cond == 1 {thenval} else {elseval}",
+            || "f(a, b, c) = if a == b {c} else {a - b}",
             |mut region| {
                 self.config.selector.enable(&mut region, 0)?;
-                region.assign_advice(|| "cond", self.config.cond, 0, || Ok(cond))?;
-                region.assign_advice(|| "thenval", self.config.thenval, 0, || Ok(thenval))?;
-                region.assign_advice(|| "elseval", self.config.elseval, 0, || Ok(elseval))?;
-
-               // let output = if cond == F::one() { thenval } else { elseval };
-
-                let one = F::one();
-                let output = if cond == one as F {thenval} else {elseval};
-
-
-                println!("cond = {:?}", cond);
-                println!("thenval = {:?}", thenval);
-                println!("elseval = {:?}", elseval);
-                println!("output = {:?}", output);
-
-
-                // let output = cond;
-                region.assign_advice(|| "output", self.config.output, 0, || Ok(output))
+                region.assign_advice(|| "a", self.config.a, 0, || a.ok_or(Error::Synthesis))?;
+                region.assign_advice(|| "b", self.config.b, 0, || b.ok_or(Error::Synthesis))?;
+                region.assign_advice(|| "c", self.config.c, 0, || c.ok_or(Error::Synthesis))?;
+
+                let diff = a.zip(b).map(|(a, b)| a - b);
+                let inv = forged_inv.or_else(|| diff.map(|diff| diff.invert().unwrap_or(F::zero())));
+                region.assign_advice(|| "a - b inv", self.config.a_minus_b_inv, 0, || inv.ok_or(Error::Synthesis))?;
+
+                let output = forged_output.or_else(|| {
+                    diff.zip(inv).zip(c).map(|((diff, inv), c)| {
+                        let is_eq = F::one() - diff * inv;
+                        is_eq * c + (F::one() - is_eq) * diff
+                    })
+                });
+                region.assign_advice(|| "output", self.config.output, 0, || output.ok_or(Error::Synthesis))
             },
         )
     }
@@ -118,9 +131,11 @@ impl<F: FieldExt> FunctionChip<F> {
 
 #[derive(Default)]
 struct FunctionCircuit<F> {
-    cond: F,
-    thenval: F,
-    elseval: F,
+    a: Option<F>,
+    b: Option<F>,
+    c: Option<F>,
+    forged_inv: Option<F>,
+    forged_output: Option<F>,
 }
 
 impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
@@ -137,39 +152,67 @@ impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
 
     fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F> ) -> Result<(), Error> {
         let chip = FunctionChip::construct(config);
-        let c = chip.assign(layouter.namespace(|| "condition"), self.cond, self.thenval, self.elseval)?;
-        chip.expose_public(layouter.namespace(|| "instance"),  &c, 0)?;
+        let out = chip.assign(layouter.namespace(|| "f(a, b, c)"), self.a, self.b, self.c, self.forged_inv, self.forged_output)?;
+        chip.expose_public(layouter.namespace(|| "instance"), &out, 0)?;
         Ok(())
     }
 }
 
-fn main() {
-
-    println!("# Example0.");
+fn f(a: Fp, b: Fp, c: Fp) -> Fp {
+    if a == b { c } else { a - b }
+}
 
-    // ANCHOR: test-circuit
+fn main() {
     // The number of rows in our circuit cannot exceed 2^k. Since our example
     // circuit is very small, we can pick a very small value here.
     let k = 4;
 
-    let cond = Fp::from(1);
-    let thenval = Fp::from(2);
-    let elseval = Fp::from(3);
+    // Both branches.
+    for (a, b, c) in [(5, 5, 9), (7, 3, 9), (3, 7, 9)] {
+        let (a, b, c) = (Fp::from(a), Fp::from(b), Fp::from(c));
+        let circuit = FunctionCircuit { a: Some(a), b: Some(b), c: Some(c), ..Default::default() };
+
+        let prover = MockProver::run(k, &circuit, vec![vec![f(a, b, c)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![f(a, b, c) + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
 
-    let output = if cond == Fp::from(1) {thenval} else {elseval};
-    println!("output = {:?}", output);
+    let (a, c) = (Fp::from(5), Fp::from(9));
+    let circuit = FunctionCircuit { a: Some(a), b: Some(a), c: Some(c), ..Default::default() };
+    println!("{}", prover::round_trip(k, &circuit, &[vec![c]]).unwrap());
 
+    // A zero inverse claims a == b for 7 != 3, so the output would be c.
+    let (a, b, c) = (Fp::from(7), Fp::from(3), Fp::from(9));
     let circuit = FunctionCircuit {
-        cond,
-        thenval,
-        elseval,
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+        forged_inv: Some(Fp::zero()),
+        ..Default::default()
     };
+    assert_gate_rejects(k, &circuit, c);
 
-    println!("# Prover is expected to return OK.");
-    let public_inputs = vec![output ];
-    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
-    assert_eq!(prover.verify(), Ok(()));
-    println!("# Done.");
+    // No inverse witness can flip a == b to the else branch: `is_eq` stays 1
+    // whatever the inverse, so an output of `a - b` breaks the output
+    // constraint even with the public input agreeing with it.
+    let (a, b, c) = (Fp::from(5), Fp::from(5), Fp::from(9));
+    let circuit = FunctionCircuit {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+        forged_inv: Some(Fp::one()),
+        forged_output: Some(a - b),
+    };
+    assert_gate_rejects(k, &circuit, a - b);
+}
 
-    // ANCHOR_END: test-circuit
-}
\ No newline at end of file
+/// Checks that `circuit`, whose output matches `public`, fails only on the
+/// gate rather than on the instance.
+fn assert_gate_rejects(k: u32, circuit: &FunctionCircuit<Fp>, public: Fp) {
+    let prover = MockProver::run(k, circuit, vec![vec![public]]).unwrap();
+    let failures = prover.verify().unwrap_err();
+    let on_gate = |failure: &VerifyFailure| matches!(failure, VerifyFailure::ConstraintNotSatisfied { .. });
+    assert!(failures.iter().all(on_gate), "{:?}", failures);
+}
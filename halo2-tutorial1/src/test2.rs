@@ -1,3 +1,5 @@
+use halo2_tutorial::prover;
+
 use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
 use std::marker::PhantomData;
 use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
@@ -118,7 +120,11 @@ impl<F: FieldExt> FibonacciChip<F> {
                     self.config.col_out,
                     0,
                     // || a_cell.value().copied() + b_cell.value(),
-                    || Ok(a_cell.value().copied().unwrap() * b_cell.value().copied().unwrap() + (one_cell.value().copied().unwrap() - a_cell.value().copied().unwrap()) * c_cell.value().copied().unwrap()),
+                    || {
+                        a_cell.value().zip(b_cell.value()).zip(one_cell.value().zip(c_cell.value()))
+                            .map(|((a, b), (one, c))| *a * *b + (*one - *a) * *c)
+                            .ok_or(Error::Synthesis)
+                    },
                 )?;
 
                 Ok(out_cell)
@@ -199,6 +205,7 @@ fn main() {
     println!("# start.");
     let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
     assert_eq!(prover.verify(),Ok(()));
+    println!("{}", prover::round_trip(k, &circuit, &[public_input]).unwrap());
     println!("# done.");
 
     // ANCHOR_END: test-circuit